@@ -0,0 +1,122 @@
+use log::debug;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::engine::scheduler::Scheduler;
+use crate::engine::statistics::Statistics;
+use crate::utils::args::Command;
+use crate::utils::config::Config;
+use crate::utils::database::{Database, Phrase, split_tags, split_translations};
+
+/// Executes a non-interactive command against the configured database.
+pub fn run(command: Command, config: &Config) -> anyhow::Result<()> {
+    debug!("Running command: {:?}", command);
+    let mut db = Database::new(&config.db_conn_string, &config.csv)?;
+
+    match command {
+        Command::Add {
+            original,
+            translation,
+            deck,
+            tags,
+        } => {
+            let translations = split_translations(&translation);
+            if translations.is_empty() {
+                anyhow::bail!("At least one translation is required.");
+            }
+            let mut phrase = Phrase::new(original, translations);
+            phrase.deck = deck;
+            phrase.tags = tags.as_deref().map(split_tags).unwrap_or_default();
+            if db.add_phrase(phrase)? {
+                println!("Phrase added.");
+            } else {
+                println!("Phrase already exists, nothing to add.");
+            }
+        }
+        Command::List => {
+            for phrase in db.get_filtered_phrases(None, &config.parse_tag_filter()?) {
+                print!(
+                    "{:>9}  [{}]  {}  =>  {}",
+                    phrase.id,
+                    phrase.deck.as_deref().unwrap_or("-"),
+                    phrase.original,
+                    phrase.translations.join(" | ")
+                );
+                if !phrase.tags.is_empty() {
+                    print!("  #{}", phrase.tags.join(" #"));
+                }
+                println!();
+            }
+        }
+        Command::Remove { id } => {
+            if !db.remove_phrase(id)? {
+                anyhow::bail!("Phrase with id {} not found.", id);
+            }
+            println!("Phrase {} removed.", id);
+        }
+        Command::Import { file } => {
+            let imported = if is_anki_file(&file) {
+                db.import_anki(&file)?
+            } else {
+                db.import_csv(&file)?
+            };
+            println!("Imported {} new phrases from {}.", imported, file.display());
+        }
+        Command::Export { file } => {
+            let exported = if is_anki_file(&file) {
+                db.export_anki(&file)?
+            } else {
+                db.export_csv(&file)?
+            };
+            println!("Exported {} phrases to {}.", exported, file.display());
+        }
+        Command::Stats => print_stats(&db, config)?,
+    }
+
+    Ok(())
+}
+
+/// Whether the file is an Anki package or plain text export rather than a CSV file.
+fn is_anki_file(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        ["apkg", "colpkg", "txt", "tsv"]
+            .iter()
+            .any(|anki| extension.eq_ignore_ascii_case(anki))
+    })
+}
+
+fn print_stats(db: &Database, config: &Config) -> anyhow::Result<()> {
+    let reviews = db.get_reviews()?;
+    let scheduler = Scheduler::new(&config.scheduler, &reviews);
+    let reviewed: HashSet<_> = reviews.iter().map(|review| review.phrase.id).collect();
+    let first_try = reviews
+        .iter()
+        .filter(|review| review.recognized && review.attempts == 1)
+        .count();
+
+    println!("Phrases:            {}", db.get_phrases().len());
+    println!("Reviewed phrases:   {}", reviewed.len());
+    println!(
+        "Never reviewed:     {}",
+        db.get_phrases().len() - reviewed.len()
+    );
+    println!("Reviews:            {}", reviews.len());
+    if !reviews.is_empty() {
+        println!(
+            "First-try accuracy: {:.0}%",
+            100.0 * first_try as f64 / reviews.len() as f64
+        );
+    }
+    let now = chrono::Local::now();
+    println!("Due for review:     {}", scheduler.count_due(now));
+    let statistics = Statistics::new(
+        db.get_phrases(),
+        &reviews,
+        &scheduler,
+        config.quiz_direction.directions(),
+        now,
+    );
+    println!("Current streak:     {} days", statistics.current_streak);
+    println!("Longest streak:     {} days", statistics.longest_streak);
+    Ok(())
+}
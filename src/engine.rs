@@ -1,3 +1,10 @@
 pub mod app;
 pub mod app_state;
+pub mod diff;
 pub mod game;
+pub mod hint;
+pub mod language;
+pub mod line_editor;
+pub mod matcher;
+pub mod scheduler;
+pub mod statistics;
@@ -1,5 +1,6 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
+use std::time::SystemTime;
 
 use super::app_state::AppState;
 use super::app_state::MainMenuState;
@@ -11,6 +12,8 @@ use crate::events::event_dispatcher::EventDispatcher;
 pub struct App {
     config: Rc<RefCell<Config>>,
     user_input: EventDispatcher,
+    /// Modification time of the phrases last seen by the main menu.
+    phrases_modified_at: Rc<Cell<Option<SystemTime>>>,
 }
 
 impl App {
@@ -18,25 +21,31 @@ impl App {
         Ok(App {
             config,
             user_input: EventDispatcher::new(),
+            phrases_modified_at: Rc::default(),
         })
     }
 
     pub fn run(&mut self) -> anyhow::Result<()> {
-        let mut current_state: Box<dyn super::app_state::AppState> =
-            Box::new(MainMenuState::new(self.config.clone())?);
+        let mut current_state: Box<dyn AppState> = Box::new(self.main_menu());
 
         loop {
             current_state.render()?;
 
-            // TODO consider non-blocking events - fluent game quiting
-            let event = self.user_input.get()?;
+            let event = self.user_input.get(current_state.tick_interval())?;
             match current_state.handle_event(event)? {
                 StateTransition::None => continue,
                 StateTransition::Quit => break Ok(()),
                 StateTransition::Transition(new_state) => {
                     current_state = new_state;
                 }
+                StateTransition::MainMenu => {
+                    current_state = Box::new(self.main_menu());
+                }
             }
         }
     }
+
+    fn main_menu(&self) -> MainMenuState {
+        MainMenuState::with_phrases_seen(self.config.clone(), self.phrases_modified_at.clone())
+    }
 }
@@ -1,17 +1,21 @@
+mod deck_picker_state;
 mod game_state;
 mod main_menu_state;
 mod quit_state;
 mod settings_state;
 mod state_transition;
+mod stats_state;
 
 pub use main_menu_state::MainMenuState;
 pub use state_transition::StateTransition;
 
+use deck_picker_state::DeckPickerState;
 use game_state::GameState;
 use quit_state::QuitState;
 use settings_state::SettingsState;
+use stats_state::StatsState;
 
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, time::Duration};
 
 use crate::{events::event::Event, utils::config::Config};
 
@@ -21,4 +25,9 @@ pub trait AppState {
         Self: Sized;
     fn handle_event(&mut self, event: Event) -> anyhow::Result<StateTransition>;
     fn render(&self) -> anyhow::Result<()>;
+    /// How often the state wants `Event::Tick` while no key is pressed, e.g. to animate
+    /// a countdown. States waiting only for keys return `None`.
+    fn tick_interval(&self) -> Option<Duration> {
+        None
+    }
 }
@@ -0,0 +1,222 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use log::{error, trace};
+
+use super::AppState;
+use super::GameState;
+use super::StateTransition;
+use super::quit_state::QuitState;
+
+use crate::engine::line_editor::LineEditor;
+use crate::events::event::Event;
+use crate::renderer::Renderer;
+use crate::utils::config::{self, Config};
+use crate::utils::database::{Database, TagFilter};
+
+#[derive(Debug, PartialEq)]
+enum PickerPhase {
+    ChoosingDeck,
+    EditingTagFilter,
+}
+
+/// Lets the user pick the deck and the tags to practise before a game starts.
+///
+/// Decks are picked with their number: with up to nine decks a single key starts the game,
+/// otherwise the number is typed and confirmed with Enter. Enter alone continues with the
+/// deck used last time.
+pub struct DeckPickerState {
+    renderer: Renderer,
+    config: Rc<RefCell<Config>>,
+    db: Database,
+
+    decks: Vec<(String, usize)>,
+    user_input: LineEditor,
+    picker_phase: PickerPhase,
+    status: Option<String>,
+}
+
+impl AppState for DeckPickerState {
+    fn new(config: Rc<RefCell<Config>>) -> anyhow::Result<Self> {
+        let db = {
+            let config = config.borrow();
+            Database::new(&config.db_conn_string, &config.csv)?
+        };
+        Ok(DeckPickerState {
+            renderer: Renderer::new(config.clone()),
+            config,
+            decks: db.get_decks(),
+            db,
+            user_input: LineEditor::new(),
+            picker_phase: PickerPhase::ChoosingDeck,
+            status: None,
+        })
+    }
+
+    fn handle_event(&mut self, event: Event) -> anyhow::Result<StateTransition> {
+        match event {
+            Event::Enter if self.picker_phase == PickerPhase::EditingTagFilter => {
+                self.apply_tag_filter();
+                Ok(StateTransition::None)
+            }
+            Event::Enter => {
+                let deck = match self.user_input.take() {
+                    Some(input) => match self.deck_by_number(&input) {
+                        Some(deck) => Some(deck),
+                        None => {
+                            trace!("Input '{}' does not correspond to any deck", input);
+                            return Ok(StateTransition::None);
+                        }
+                    },
+                    None => self.last_deck(),
+                };
+                self.start_game(deck)
+            }
+            Event::Back if self.picker_phase == PickerPhase::EditingTagFilter => {
+                trace!("Tag filter editing cancelled");
+                self.user_input.clear();
+                self.picker_phase = PickerPhase::ChoosingDeck;
+                Ok(StateTransition::None)
+            }
+            Event::Back => {
+                trace!("Going back to main menu");
+                Ok(StateTransition::MainMenu)
+            }
+            Event::Quit => {
+                trace!("Quitting application");
+                let quit_state = QuitState::new(self.config.clone())?;
+                Ok(StateTransition::Transition(Box::new(quit_state)))
+            }
+            Event::Edit(edit) => {
+                self.user_input.edit(edit);
+                Ok(StateTransition::None)
+            }
+            Event::Tick | Event::Hint | Event::Resize => Ok(StateTransition::None),
+            Event::Character(c) if self.picker_phase == PickerPhase::EditingTagFilter => {
+                self.user_input.insert(c);
+                Ok(StateTransition::None)
+            }
+            Event::Character(c) if c.is_ascii_digit() => {
+                self.user_input.insert(c);
+                if self.decks.len() <= 9 {
+                    let input = self.user_input.take().unwrap_or_default();
+                    match self.deck_by_number(&input) {
+                        Some(deck) => return self.start_game(Some(deck)),
+                        None => trace!("Input '{}' does not correspond to any deck", input),
+                    }
+                }
+                Ok(StateTransition::None)
+            }
+            Event::Character(c) => match c.to_lowercase().next() {
+                Some('a') => self.start_game(None),
+                Some('t') => {
+                    trace!("User selected to change the tag filter");
+                    let tag_filter = self.config.borrow().active_tag_filter().map(str::to_string);
+                    self.user_input.set_text(&tag_filter.unwrap_or_default());
+                    self.status = None;
+                    self.picker_phase = PickerPhase::EditingTagFilter;
+                    Ok(StateTransition::None)
+                }
+                Some('b') => {
+                    trace!("Going back to main menu");
+                    Ok(StateTransition::MainMenu)
+                }
+                _ => {
+                    trace!("User input '{}' does not correspond to any option", c);
+                    Ok(StateTransition::None)
+                }
+            },
+        }
+    }
+
+    fn render(&self) -> anyhow::Result<()> {
+        let matching = match self.config.borrow().parse_tag_filter() {
+            Ok(filter) => self.db.get_filtered_phrases(None, &filter).len(),
+            Err(_) => 0,
+        };
+        self.renderer.render_deck_picker(
+            &self.decks,
+            matching,
+            self.last_deck().as_deref(),
+            &self.user_input,
+            self.picker_phase == PickerPhase::EditingTagFilter,
+            self.status.as_deref(),
+        )
+    }
+}
+
+impl DeckPickerState {
+    /// Whether there is anything to pick: several decks or tags to filter by.
+    pub fn has_choice(&self) -> bool {
+        self.decks.len() > 1
+            || self.config.borrow().tag_filter.is_some()
+            || self
+                .db
+                .get_phrases()
+                .iter()
+                .any(|phrase| !phrase.tags.is_empty())
+    }
+
+    /// Returns the remembered deck if it still exists.
+    fn last_deck(&self) -> Option<String> {
+        let deck = self.config.borrow().deck.clone()?;
+        self.decks
+            .iter()
+            .any(|(name, _)| *name == deck)
+            .then_some(deck)
+    }
+
+    fn deck_by_number(&self, input: &str) -> Option<String> {
+        let idx = input.parse::<usize>().ok()?.checked_sub(1)?;
+        self.decks.get(idx).map(|(name, _)| name.clone())
+    }
+
+    fn apply_tag_filter(&mut self) {
+        let tag_filter = self.user_input.take();
+        trace!("User submitted tag filter: {:?}", tag_filter);
+        let mut config = self.config.borrow_mut();
+        if let Err(e) = tag_filter
+            .as_deref()
+            .unwrap_or_default()
+            .parse::<TagFilter>()
+        {
+            self.status = Some(e.to_string());
+        } else {
+            config.tag_filter = tag_filter;
+            config.session_tag_filter = None;
+        }
+        self.picker_phase = PickerPhase::ChoosingDeck;
+    }
+
+    fn start_game(&mut self, deck: Option<String>) -> anyhow::Result<StateTransition> {
+        let tag_filter = self.config.borrow().parse_tag_filter()?;
+        if self
+            .db
+            .get_filtered_phrases(deck.as_deref(), &tag_filter)
+            .is_empty()
+        {
+            trace!("No phrases in deck {:?} match {:?}", deck, tag_filter);
+            self.status = Some("No phrases match the tag filter in this deck.".to_string());
+            return Ok(StateTransition::None);
+        }
+
+        trace!("Starting game with deck {:?}", deck);
+        self.config.borrow_mut().deck = deck.clone();
+
+        // Only the picked deck and tags are written back, other unsaved changes and the tag
+        // filter of the command line stay in memory
+        let path = self.config.borrow().path.clone();
+        let tag_filter = self.config.borrow().tag_filter.clone();
+        let result = config::load(&path).and_then(|mut stored| {
+            stored.deck = deck;
+            stored.tag_filter = tag_filter;
+            config::save(&stored)
+        });
+        if let Err(e) = result {
+            error!("Failed to remember the deck: {:?}", e);
+        }
+
+        let game_state = GameState::new(self.config.clone())?;
+        Ok(StateTransition::Transition(Box::new(game_state)))
+    }
+}
@@ -2,22 +2,25 @@ use log::{error, trace};
 
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::Duration;
 
 use super::AppState;
 use super::StateTransition;
 use super::quit_state::QuitState;
 
-use crate::engine::app_state::MainMenuState;
-use crate::engine::game::Game;
-use crate::events::event::Event;
+use crate::engine::game::{Game, RoundSummary};
+use crate::engine::line_editor::LineEditor;
+use crate::engine::matcher::MatchGrade;
+use crate::events::event::{Edit, Event};
 use crate::renderer::Renderer;
+use crate::types::GameMode;
 use crate::utils::config::Config;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug)]
 enum GamePhase {
     Input,
-    Feedback(bool),
-    RoundEnd,
+    Feedback(MatchGrade),
+    RoundEnd(RoundSummary),
 }
 
 pub struct GameState {
@@ -25,8 +28,10 @@ pub struct GameState {
     renderer: Renderer,
     config: Rc<RefCell<Config>>,
 
-    user_input: Option<String>,
+    user_input: LineEditor,
     game_phase: GamePhase,
+    game_mode: GameMode,
+    choices: Vec<String>,
 }
 
 impl AppState for GameState {
@@ -34,13 +39,18 @@ impl AppState for GameState {
         let mut game = Game::new(config.clone())?;
         game.start_round()?;
 
-        Ok(GameState {
+        let game_mode = config.borrow().game_mode;
+        let mut game_state = GameState {
             game,
             renderer: Renderer::new(config.clone()),
             config: config.clone(),
-            user_input: None,
+            user_input: LineEditor::new(),
             game_phase: GamePhase::Input,
-        })
+            game_mode,
+            choices: Vec::new(),
+        };
+        game_state.enter_input_phase()?;
+        Ok(game_state)
     }
 
     fn handle_event(&mut self, event: Event) -> anyhow::Result<StateTransition> {
@@ -51,22 +61,33 @@ impl AppState for GameState {
             }
             Event::Back => {
                 trace!("Going back to main menu");
-                let main_menu_state = MainMenuState::new(self.config.clone())?;
-                Ok(StateTransition::Transition(Box::new(main_menu_state)))
+                Ok(StateTransition::MainMenu)
             }
             Event::Quit => {
                 trace!("Quitting application");
                 let quit_state = QuitState::new(self.config.clone())?;
                 Ok(StateTransition::Transition(Box::new(quit_state)))
             }
-            Event::RemoveCharacter => {
-                trace!("Handling RemoveCharacter event");
-                self.handle_remove_character_event()
+            Event::Edit(edit) => {
+                trace!("Handling {:?} event", edit);
+                self.handle_edit_event(edit)
             }
             Event::Character(c) => {
                 trace!("Handling character input: '{}'", c);
                 self.handle_character_event(c)
             }
+            Event::Tick => {
+                if matches!(self.game_phase, GamePhase::Input) && self.is_out_of_time() {
+                    trace!("Countdown ran out");
+                    self.game_phase = GamePhase::Feedback(self.game.time_out()?);
+                }
+                Ok(StateTransition::None)
+            }
+            Event::Hint => {
+                trace!("Handling Hint event");
+                self.handle_hint_event()
+            }
+            Event::Resize => Ok(StateTransition::None),
         }
     }
 
@@ -74,28 +95,74 @@ impl AppState for GameState {
         match self.game_phase {
             GamePhase::Input => {
                 trace!("Rendering active game state");
-                let phrase = self.game.get_current_original()?;
-                self.renderer
-                    .render_guessing_screen(phrase, self.user_input.as_deref())
+                let current = self.game.get_current_phrase()?;
+                match self.game_mode {
+                    GameMode::Typed => self.renderer.render_guessing_screen(
+                        &current.phrase,
+                        current.direction,
+                        &self.user_input,
+                        current.hint().as_deref(),
+                        None,
+                    ),
+                    GameMode::Timed => self.renderer.render_guessing_screen(
+                        &current.phrase,
+                        current.direction,
+                        &self.user_input,
+                        current.hint().as_deref(),
+                        Some((self.game.time_left(self.time_limit()), self.time_limit())),
+                    ),
+                    GameMode::MultipleChoice => self.renderer.render_choice_screen(
+                        &current.phrase,
+                        current.direction,
+                        &self.choices,
+                    ),
+                }
             }
-            GamePhase::Feedback(is_correct) => {
-                trace!("Rendering feedback screen, is_correct={}", is_correct);
-                let correct_answer = self.game.get_current_translation()?;
-                self.renderer
-                    .render_feedback_screen(is_correct, correct_answer)
+            GamePhase::Feedback(grade) => {
+                trace!("Rendering feedback screen, grade={:?}", grade);
+                let current = self.game.get_current_phrase()?;
+                let diff = match self.game_mode {
+                    GameMode::Typed | GameMode::Timed
+                        if grade != MatchGrade::Exact && !self.user_input.is_empty() =>
+                    {
+                        Some(self.game.diff_answer(self.user_input.text())?)
+                    }
+                    _ => None,
+                };
+                self.renderer.render_feedback_screen(
+                    grade,
+                    &current.phrase,
+                    current.direction,
+                    diff.as_deref(),
+                )
             }
-            GamePhase::RoundEnd => {
+            GamePhase::RoundEnd(ref summary) => {
                 trace!("Rendering round end screen");
-                self.renderer.render_round_end_screen()
+                let score =
+                    (self.game_mode == GameMode::Timed).then(|| summary.score(self.time_limit()));
+                self.renderer.render_round_end_screen(summary, score)
             }
         }
     }
+
+    /// Ticks while a countdown is shown, whenever the number of seconds left changes.
+    fn tick_interval(&self) -> Option<Duration> {
+        if self.game_mode != GameMode::Timed || !matches!(self.game_phase, GamePhase::Input) {
+            return None;
+        }
+        let time_left = self.game.time_left(self.time_limit());
+        let until_next_second = time_left.as_nanos() % 1_000_000_000;
+        Some(match until_next_second {
+            0 => Duration::from_secs(1),
+            nanos => Duration::from_nanos(nanos as u64),
+        })
+    }
 }
 
 impl Drop for GameState {
     fn drop(&mut self) {
         trace!("Dropping GameState and cleaning up resources");
-        if self.game_phase != GamePhase::RoundEnd {
+        if !matches!(self.game_phase, GamePhase::RoundEnd(_)) {
             trace!("Ending active game round before dropping GameState");
             if let Err(e) = self.game.end_round() {
                 error!("Error ending game round during GameState drop: {:?}", e);
@@ -106,57 +173,84 @@ impl Drop for GameState {
 
 impl GameState {
     fn handle_submit_event(&mut self) -> anyhow::Result<StateTransition> {
-        trace!("User submitted input: {:?}", self.user_input);
-        match self.game_phase {
+        trace!("User submitted input: {:?}", self.user_input.text());
+        match &self.game_phase {
+            GamePhase::Input
+                if self.game_mode == GameMode::MultipleChoice && self.user_input.is_empty() =>
+            {
+                trace!("No choice picked yet, ignoring submit")
+            }
+            GamePhase::Input if self.is_out_of_time() => {
+                trace!("Answer submitted after the countdown ran out");
+                self.game_phase = GamePhase::Feedback(self.game.time_out()?);
+            }
             GamePhase::Input => {
                 trace!("Checking user input against current phrase");
-                let is_correct = if let Some(input) = &self.user_input {
-                    self.game.check_phrase(input)?
-                } else {
-                    false
+                let input = self.user_input.text();
+                let grade = match self.game_mode {
+                    _ if input.is_empty() => MatchGrade::Wrong,
+                    GameMode::Typed | GameMode::Timed => self.game.check_phrase(input)?,
+                    GameMode::MultipleChoice => self.game.check_choice(input)?,
                 };
-                self.game_phase = GamePhase::Feedback(is_correct);
-            }
-            GamePhase::Feedback(is_correct) => {
-                trace!(
-                    "Advancing game state based on feedback: is_correct={}",
-                    is_correct
-                );
-                if self.game.advance_phrase(is_correct).is_err() {
+                self.game_phase = GamePhase::Feedback(grade);
+            }
+            GamePhase::Feedback(grade) => {
+                trace!("Advancing game state based on feedback: grade={:?}", grade);
+                self.user_input.clear();
+                if self.game.advance_phrase(grade.is_correct()).is_err() {
                     trace!("No more phrases available, ending round");
-                    self.game.end_round()?;
-                    self.game_phase = GamePhase::RoundEnd;
+                    let summary = self.game.end_round()?;
+                    self.game_phase = GamePhase::RoundEnd(summary);
                 } else {
-                    self.game_phase = GamePhase::Input;
+                    self.enter_input_phase()?;
                 }
             }
-            GamePhase::RoundEnd => {
+            GamePhase::RoundEnd(_) => {
                 trace!("Round has ended, starting new round");
                 self.game.start_round()?;
-                self.game_phase = GamePhase::Input;
+                self.enter_input_phase()?;
             }
         }
 
-        self.user_input = None;
         Ok(StateTransition::None)
     }
 
     fn handle_character_event(&mut self, c: char) -> anyhow::Result<StateTransition> {
         match self.game_phase {
+            GamePhase::Input if self.game_mode == GameMode::MultipleChoice => {
+                let choice = c
+                    .to_digit(10)
+                    .and_then(|digit| (digit as usize).checked_sub(1))
+                    .and_then(|idx| self.choices.get(idx));
+                if let Some(choice) = choice {
+                    trace!("User picked choice '{}'", choice);
+                    self.user_input.set_text(choice);
+                    return self.handle_submit_event();
+                }
+                trace!("Input '{}' does not correspond to any choice", c);
+            }
             GamePhase::Input => {
                 trace!("Adding character '{}' to user input", c);
-                if let Some(input) = &mut self.user_input {
-                    input.push(c);
-                } else {
-                    self.user_input = Some(c.to_string());
-                }
+                self.user_input.insert(c);
             }
-            GamePhase::RoundEnd => {
+            GamePhase::RoundEnd(ref summary) => {
                 trace!("RoundEnd phase: character input '{}'", c);
-                if c.to_lowercase().next() == Some('b') {
-                    trace!("Going back to main menu from round end screen");
-                    let main_menu = MainMenuState::new(self.config.clone())?;
-                    return Ok(StateTransition::Transition(Box::new(main_menu)));
+                match c.to_lowercase().next() {
+                    Some('b') => {
+                        trace!("Going back to main menu from round end screen");
+                        return Ok(StateTransition::MainMenu);
+                    }
+                    Some('r') => {
+                        let phrases = summary.phrases_to_redrill();
+                        if phrases.is_empty() {
+                            trace!("No phrases to re-drill");
+                        } else {
+                            trace!("Re-drilling {} phrases", phrases.len());
+                            self.game.start_drill(phrases)?;
+                            self.enter_input_phase()?;
+                        }
+                    }
+                    _ => (),
                 }
             }
             GamePhase::Feedback(_) => {
@@ -166,21 +260,49 @@ impl GameState {
         Ok(StateTransition::None)
     }
 
-    fn handle_remove_character_event(&mut self) -> anyhow::Result<StateTransition> {
-        if self.game_phase != GamePhase::Input {
-            trace!("Cannot modify input, game is not in input phase");
+    fn handle_hint_event(&mut self) -> anyhow::Result<StateTransition> {
+        if !matches!(self.game_phase, GamePhase::Input)
+            || self.game_mode == GameMode::MultipleChoice
+        {
+            trace!("Hints are only given while typing an answer");
             return Ok(StateTransition::None);
         }
 
-        if let Some(input) = &mut self.user_input {
-            input.pop();
-            if input.is_empty() {
-                self.user_input = None;
-            }
-        } else {
-            trace!("User input is empty, cannot remove character");
+        if !self.game.reveal_hint()? {
+            trace!("All hints revealed already");
+        }
+        Ok(StateTransition::None)
+    }
+
+    fn time_limit(&self) -> Duration {
+        Duration::from_secs(self.config.borrow().time_limit)
+    }
+
+    fn is_out_of_time(&self) -> bool {
+        self.game_mode == GameMode::Timed && self.game.time_left(self.time_limit()).is_zero()
+    }
+
+    /// Shows the current phrase, the answer given to the previous one is kept until then
+    /// to compare it with the expected one on the feedback screen.
+    fn enter_input_phase(&mut self) -> anyhow::Result<()> {
+        self.game_phase = GamePhase::Input;
+        self.user_input.clear();
+        if self.game_mode == GameMode::MultipleChoice {
+            let choices_count = self.config.borrow().choices_count;
+            self.choices = self.game.get_choices(choices_count)?;
+        }
+        Ok(())
+    }
+
+    fn handle_edit_event(&mut self, edit: Edit) -> anyhow::Result<StateTransition> {
+        if !matches!(self.game_phase, GamePhase::Input)
+            || self.game_mode == GameMode::MultipleChoice
+        {
+            trace!("Cannot modify input, game is not in input phase");
+            return Ok(StateTransition::None);
         }
 
+        self.user_input.edit(edit);
         Ok(StateTransition::None)
     }
 }
@@ -1,35 +1,93 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
+use std::time::{Duration, SystemTime};
 
 use log::{trace, warn};
 
 use super::AppState;
+use super::DeckPickerState;
 use super::GameState;
 use super::QuitState;
 use super::SettingsState;
 use super::StateTransition;
+use super::StatsState;
 
 use crate::events::event::Event;
 use crate::renderer::Renderer;
 use crate::utils::config::Config;
+use crate::utils::database::Database;
 
+/// How often the phrases are checked for changes while the main menu is shown.
+const PHRASES_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The main menu, noting when the phrases changed on disk since it was last shown or while
+/// it's shown. Only phrase files are watched, SQLite databases can't tell when their phrases
+/// changed, see `Database::phrases_modified_at`.
 pub struct MainMenuState {
     config: Rc<RefCell<Config>>,
     renderer: Renderer,
+    /// Modification time of the phrases last seen, shared with the main menus shown later.
+    phrases_modified_at: Rc<Cell<Option<SystemTime>>>,
+    /// Whether the phrases changed on disk since the main menu was last shown.
+    phrases_changed: bool,
 }
 
-impl AppState for MainMenuState {
-    fn new(config: Rc<RefCell<Config>>) -> anyhow::Result<Self> {
-        Ok(MainMenuState {
+impl MainMenuState {
+    pub fn with_phrases_seen(
+        config: Rc<RefCell<Config>>,
+        phrases_modified_at: Rc<Cell<Option<SystemTime>>>,
+    ) -> Self {
+        let mut state = MainMenuState {
             renderer: Renderer::new(config.clone()),
             config,
-        })
+            phrases_modified_at,
+            phrases_changed: false,
+        };
+        state.check_phrases();
+        state
+    }
+
+    /// Notes if the phrases were modified since they were last seen. Storages that can't
+    /// tell are never reported as changed, and failing to check isn't reported here either,
+    /// as it's reported once a game starts.
+    fn check_phrases(&mut self) {
+        let modified_at = match Database::phrases_modified_at(&self.config.borrow().db_conn_string)
+        {
+            Ok(modified_at) => modified_at,
+            Err(e) => {
+                warn!("Failed to check phrases for changes: {:?}", e);
+                return;
+            }
+        };
+        let previous = self.phrases_modified_at.replace(modified_at);
+        if previous.is_some() && modified_at.is_some() && previous != modified_at {
+            trace!(
+                "Phrases modified at {:?}, previously {:?}",
+                modified_at, previous
+            );
+            self.phrases_changed = true;
+        }
+    }
+}
+
+impl AppState for MainMenuState {
+    /// Builds a main menu that hasn't seen the phrases before, `App` builds the ones that
+    /// notice changes between visits.
+    fn new(config: Rc<RefCell<Config>>) -> anyhow::Result<Self> {
+        Ok(MainMenuState::with_phrases_seen(config, Rc::default()))
     }
 
     fn handle_event(&mut self, event: Event) -> anyhow::Result<StateTransition> {
         match event {
             Event::Enter => {
+                let deck_picker_state = DeckPickerState::new(self.config.clone())?;
+                if deck_picker_state.has_choice() {
+                    trace!("Transitioning to deck picker state");
+                    return Ok(StateTransition::Transition(Box::new(deck_picker_state)));
+                }
+
                 trace!("Creating new game state");
+                self.config.borrow_mut().deck = None;
                 let game_state = GameState::new(self.config.clone())?;
                 return Ok(StateTransition::Transition(Box::new(game_state)));
             }
@@ -39,11 +97,21 @@ impl AppState for MainMenuState {
                 return Ok(StateTransition::Transition(Box::new(quit_state)));
             }
             Event::Character(c) => match c.to_lowercase().next() {
+                Some('m') => {
+                    let mut config = self.config.borrow_mut();
+                    config.game_mode = config.game_mode.next();
+                    trace!("Game mode changed to {:?}", config.game_mode);
+                }
                 Some('s') => {
                     trace!("Transitioning to settings state");
                     let settings_state = SettingsState::new(self.config.clone())?;
                     return Ok(StateTransition::Transition(Box::new(settings_state)));
                 }
+                Some('t') => {
+                    trace!("Transitioning to statistics state");
+                    let stats_state = StatsState::new(self.config.clone())?;
+                    return Ok(StateTransition::Transition(Box::new(stats_state)));
+                }
                 Some('q') => {
                     trace!("Quitting application");
                     let quit_state = QuitState::new(self.config.clone())?;
@@ -53,6 +121,7 @@ impl AppState for MainMenuState {
                     trace!("Unhandled character input in main menu: {}", c);
                 }
             },
+            Event::Tick => self.check_phrases(),
             _ => {
                 warn!("Unhandled event: {:?}", event);
             }
@@ -62,6 +131,11 @@ impl AppState for MainMenuState {
     }
 
     fn render(&self) -> anyhow::Result<()> {
-        self.renderer.render_main_menu()
+        self.renderer
+            .render_main_menu(self.config.borrow().game_mode, self.phrases_changed)
+    }
+
+    fn tick_interval(&self) -> Option<Duration> {
+        Some(PHRASES_CHECK_INTERVAL)
     }
 }
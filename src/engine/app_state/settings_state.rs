@@ -1,21 +1,22 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use log::trace;
+use log::{error, trace};
 
+use crate::engine::line_editor::LineEditor;
 use crate::events::event::Event;
 use crate::renderer::Renderer;
-use crate::utils::config::Config;
+use crate::utils::config::{self, Config, FIELDS, field_index};
 
 use super::AppState;
 use super::StateTransition;
-use super::main_menu_state::MainMenuState;
 use super::quit_state::QuitState;
 
 #[derive(Debug, PartialEq)]
 enum SettingsPhase {
     ChoosingOption,
-    ChangingOption(char),
+    /// Typing a new value of the field at the given index of `FIELDS`.
+    ChangingOption(usize),
 }
 
 pub struct SettingsState {
@@ -23,8 +24,11 @@ pub struct SettingsState {
     config: Rc<RefCell<Config>>,
     config_clone: Config,
 
-    user_input: Option<String>,
+    user_input: LineEditor,
     settings_phase: SettingsPhase,
+    /// Index of the field changed most recently, highlighted along with its help text.
+    selected_field: Option<usize>,
+    status: Option<String>,
 }
 
 impl AppState for SettingsState {
@@ -33,8 +37,10 @@ impl AppState for SettingsState {
             renderer: Renderer::new(config.clone()),
             config: config.clone(),
             config_clone: config.borrow().clone(),
-            user_input: None,
+            user_input: LineEditor::new(),
             settings_phase: SettingsPhase::ChoosingOption,
+            selected_field: None,
+            status: None,
         })
     }
 
@@ -46,46 +52,33 @@ impl AppState for SettingsState {
             }
             Event::Back => {
                 trace!("Going back to main menu");
-                let main_menu_state = MainMenuState::new(self.config.clone())?;
-                return Ok(StateTransition::Transition(Box::new(main_menu_state)));
+                return Ok(StateTransition::MainMenu);
             }
             Event::Quit => {
                 trace!("Quitting application");
                 let quit_state = QuitState::new(self.config.clone())?;
                 return Ok(StateTransition::Transition(Box::new(quit_state)));
             }
-            Event::RemoveCharacter => {
-                if let Some(input) = &mut self.user_input {
-                    input.pop();
-                    if input.is_empty() {
-                        self.user_input = None;
-                    }
-                } else {
-                    trace!("User input is empty, cannot remove character");
-                }
+            Event::Edit(edit) if self.settings_phase != SettingsPhase::ChoosingOption => {
+                self.user_input.edit(edit);
             }
             Event::Character(c) => {
                 trace!("Handling character input: '{}'", c);
                 return self.handle_character_event(c);
             }
+            Event::Edit(_) | Event::Tick | Event::Hint | Event::Resize => (),
         };
 
         Ok(StateTransition::None)
     }
 
     fn render(&self) -> anyhow::Result<()> {
-        // TODO some sort of indication of which option is being changed
-        let placeholder_text = match self.settings_phase {
-            SettingsPhase::ChoosingOption => None,
-            SettingsPhase::ChangingOption(option) => match option {
-                'p' => Some("Enter number of phrases per round..."),
-                _ => Some("Sorry, something went wrong..."),
-            },
-        };
         self.renderer.render_settings_menu(
-            self.user_input.as_deref(),
-            placeholder_text,
             &self.config_clone,
+            self.selected_field.map(|idx| &FIELDS[idx]),
+            &self.user_input,
+            matches!(self.settings_phase, SettingsPhase::ChangingOption(_)),
+            self.status.as_deref(),
         )
     }
 }
@@ -94,56 +87,76 @@ impl SettingsState {
     fn handle_submit_event(&mut self) -> anyhow::Result<StateTransition> {
         match self.settings_phase {
             SettingsPhase::ChoosingOption => trace!("User submitted input while choosing option"), // No-op
-            SettingsPhase::ChangingOption(_) => {
-                // TODO implement better way to update settings
+            SettingsPhase::ChangingOption(idx) => {
+                let field = &FIELDS[idx];
                 trace!(
-                    "User submitted input while changing option: {:?}",
-                    self.user_input
+                    "User submitted input while changing {}: {:?}",
+                    field.name,
+                    self.user_input.text()
                 );
-                let parsed = self
-                    .user_input
-                    .as_ref()
-                    .and_then(|input| input.parse::<usize>().ok());
-                if let Some(value) = parsed {
-                    self.config_clone.phrases_per_round = value;
-                    trace!("Updated phrases_per_round to {}", value);
-                } else {
-                    trace!("Invalid input for phrases_per_round: {:?}", self.user_input);
-                }
-
-                self.user_input = None;
+                let value = self.user_input.take().unwrap_or_default();
+                self.update_field(idx, &value);
                 self.settings_phase = SettingsPhase::ChoosingOption;
             }
         }
         Ok(StateTransition::None)
     }
 
+    fn update_field(&mut self, idx: usize, value: &str) {
+        let field = &FIELDS[idx];
+        self.selected_field = Some(idx);
+        self.status = match field.update(&mut self.config_clone, value) {
+            Ok(()) => {
+                trace!("Updated {} to {:?}", field.name, value);
+                None
+            }
+            Err(e) => {
+                trace!("Invalid input for {}: {:?}", field.name, e);
+                Some(e.to_string())
+            }
+        };
+    }
+
     fn handle_character_event(&mut self, c: char) -> anyhow::Result<StateTransition> {
         match self.settings_phase {
             SettingsPhase::ChoosingOption => match c.to_lowercase().next() {
-                Some('p') => {
-                    trace!("User selected to change phrase source");
-                    self.settings_phase = SettingsPhase::ChangingOption('p');
-                    return Ok(StateTransition::None);
-                }
                 Some('s') => {
                     trace!("User selected to save settings");
                     *self.config.borrow_mut() = self.config_clone.clone();
+                    self.status = match config::save(&self.config_clone) {
+                        Ok(()) => Some(format!("Saved to {}", self.config_clone.path.display())),
+                        Err(e) => {
+                            error!("Failed to save settings: {:?}", e);
+                            Some(format!("Failed to save settings: {}", e))
+                        }
+                    };
                     return Ok(StateTransition::None);
                 }
                 Some('b') => {
                     trace!("User selected to go back to the main menu");
-                    let main_menu_state = MainMenuState::new(self.config.clone())?;
-                    return Ok(StateTransition::Transition(Box::new(main_menu_state)));
+                    return Ok(StateTransition::MainMenu);
                 }
-                _ => trace!("User input '{}' does not correspond to any option", c),
+                Some(key) => match field_index(key) {
+                    Some(idx) => {
+                        let field = &FIELDS[idx];
+                        if let Some(value) = field.next_value(&self.config_clone) {
+                            self.update_field(idx, &value);
+                        } else {
+                            trace!("User selected to change {}", field.name);
+                            let value = field.value(&self.config_clone);
+                            self.user_input.set_text(&value);
+                            self.selected_field = Some(idx);
+                            self.status = None;
+                            self.settings_phase = SettingsPhase::ChangingOption(idx);
+                        }
+                        return Ok(StateTransition::None);
+                    }
+                    None => trace!("User input '{}' does not correspond to any option", c),
+                },
+                None => trace!("User input '{}' does not correspond to any option", c),
             },
             SettingsPhase::ChangingOption(_) => {
-                if let Some(input) = &mut self.user_input {
-                    input.push(c);
-                } else {
-                    self.user_input = Some(c.to_string());
-                }
+                self.user_input.insert(c);
                 return Ok(StateTransition::None);
             }
         }
@@ -3,5 +3,8 @@ use super::AppState;
 pub enum StateTransition {
     None,
     Transition(Box<dyn AppState>),
+    /// Goes back to the main menu, which `App` builds as it keeps track of the phrases it
+    /// has seen.
+    MainMenu,
     Quit,
 }
@@ -0,0 +1,69 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use log::trace;
+
+use super::AppState;
+use super::StateTransition;
+use super::quit_state::QuitState;
+
+use crate::engine::scheduler::Scheduler;
+use crate::engine::statistics::Statistics;
+use crate::events::event::Event;
+use crate::renderer::Renderer;
+use crate::utils::config::Config;
+use crate::utils::database::Database;
+
+pub struct StatsState {
+    renderer: Renderer,
+    config: Rc<RefCell<Config>>,
+    statistics: Statistics,
+}
+
+impl AppState for StatsState {
+    fn new(config: Rc<RefCell<Config>>) -> anyhow::Result<Self> {
+        let statistics = {
+            let config = config.borrow();
+            let db = Database::new(&config.db_conn_string, &config.csv)?;
+            let reviews = db.get_reviews()?;
+            let scheduler = Scheduler::new(&config.scheduler, &reviews);
+            Statistics::new(
+                db.get_phrases(),
+                &reviews,
+                &scheduler,
+                config.quiz_direction.directions(),
+                chrono::Local::now(),
+            )
+        };
+
+        Ok(StatsState {
+            renderer: Renderer::new(config.clone()),
+            config,
+            statistics,
+        })
+    }
+
+    fn handle_event(&mut self, event: Event) -> anyhow::Result<StateTransition> {
+        match event {
+            Event::Quit => {
+                trace!("Quitting application");
+                let quit_state = QuitState::new(self.config.clone())?;
+                Ok(StateTransition::Transition(Box::new(quit_state)))
+            }
+            Event::Character(c) if c.to_lowercase().next() == Some('q') => {
+                trace!("Quitting application");
+                let quit_state = QuitState::new(self.config.clone())?;
+                Ok(StateTransition::Transition(Box::new(quit_state)))
+            }
+            Event::Enter | Event::Back | Event::Character(_) => {
+                trace!("Going back to main menu");
+                Ok(StateTransition::MainMenu)
+            }
+            Event::Edit(_) | Event::Tick | Event::Hint | Event::Resize => Ok(StateTransition::None),
+        }
+    }
+
+    fn render(&self) -> anyhow::Result<()> {
+        self.renderer.render_statistics_screen(&self.statistics)
+    }
+}
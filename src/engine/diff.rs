@@ -0,0 +1,207 @@
+/// Difference between a word of the user's answer and the expected answer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WordDiff {
+    Same(String),
+    /// A misspelled word, with its letters compared to the expected spelling.
+    Typo(Vec<CharDiff>),
+    /// A word of the answer that isn't expected.
+    Extra(String),
+    /// An expected word left out of the answer.
+    Missing(String),
+    /// An expected word given in the wrong place.
+    Moved(String),
+}
+
+/// Difference between letters of a misspelled word and its expected spelling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CharDiff {
+    Same(char),
+    Extra(char),
+    Missing(char),
+}
+
+/// Step of the alignment of two sequences, with the index of the item it consumes; the
+/// given one for matching items.
+enum Step {
+    Same(usize),
+    Extra(usize),
+    Missing(usize),
+}
+
+/// Compares the answer with the expected one word by word, in the order of the answer.
+///
+/// Words are compared with `same`, so they're told apart the same way the answer was
+/// graded. Unmatched words standing in the same place are reported as typos when
+/// they're alike, and an extra word expected somewhere else as moved.
+pub fn diff(answer: &str, expected: &str, same: impl Fn(&str, &str) -> bool) -> Vec<WordDiff> {
+    let answer: Vec<&str> = answer.split_whitespace().collect();
+    let expected: Vec<&str> = expected.split_whitespace().collect();
+
+    let mut words = Vec::new();
+    let mut extra = Vec::new();
+    let mut missing = Vec::new();
+    for step in align(&answer, &expected, |a, b| same(a, b)) {
+        match step {
+            Step::Same(idx) => {
+                flush_gap(&mut words, &mut extra, &mut missing, &answer, &expected);
+                words.push(WordDiff::Same(answer[idx].to_string()));
+            }
+            Step::Extra(idx) => extra.push(idx),
+            Step::Missing(idx) => missing.push(idx),
+        }
+    }
+    flush_gap(&mut words, &mut extra, &mut missing, &answer, &expected);
+
+    // Words left out in one place and given in another one were only moved
+    let mut moved_from = Vec::new();
+    for idx in 0..words.len() {
+        let WordDiff::Extra(word) = &words[idx] else {
+            continue;
+        };
+        let found = words.iter().enumerate().position(|(other_idx, other)| {
+            !moved_from.contains(&other_idx)
+                && matches!(other, WordDiff::Missing(missing) if same(word, missing))
+        });
+        if let Some(found) = found {
+            moved_from.push(found);
+            words[idx] = WordDiff::Moved(word.clone());
+        }
+    }
+    let mut idx = 0;
+    words.retain(|_| {
+        idx += 1;
+        !moved_from.contains(&(idx - 1))
+    });
+    words
+}
+
+/// Adds the words between two matching ones, pairing them up in order.
+fn flush_gap(
+    words: &mut Vec<WordDiff>,
+    extra: &mut Vec<usize>,
+    missing: &mut Vec<usize>,
+    answer: &[&str],
+    expected: &[&str],
+) {
+    for idx in 0..extra.len().max(missing.len()) {
+        let given = extra.get(idx).map(|&idx| answer[idx]);
+        let wanted = missing.get(idx).map(|&idx| expected[idx]);
+        match (given, wanted) {
+            (Some(given), Some(wanted)) if is_typo(given, wanted) => {
+                words.push(WordDiff::Typo(diff_chars(given, wanted)))
+            }
+            (given, wanted) => {
+                words.extend(given.map(|word| WordDiff::Extra(word.to_string())));
+                words.extend(wanted.map(|word| WordDiff::Missing(word.to_string())));
+            }
+        }
+    }
+    extra.clear();
+    missing.clear();
+}
+
+/// Tells whether the words are alike enough to be the same word misspelled, which is when
+/// at least half of the letters are the same.
+fn is_typo(given: &str, wanted: &str) -> bool {
+    let same = diff_chars(given, wanted)
+        .iter()
+        .filter(|c| matches!(c, CharDiff::Same(_)))
+        .count();
+    2 * same >= given.chars().count().max(wanted.chars().count())
+}
+
+/// Compares the letters of a misspelled word with the expected spelling, ignoring case.
+fn diff_chars(given: &str, wanted: &str) -> Vec<CharDiff> {
+    let given: Vec<char> = given.chars().collect();
+    let wanted: Vec<char> = wanted.chars().collect();
+    align(&given, &wanted, |a, b| {
+        a.to_lowercase().eq(b.to_lowercase())
+    })
+    .into_iter()
+    .map(|step| match step {
+        Step::Same(idx) => CharDiff::Same(given[idx]),
+        Step::Extra(idx) => CharDiff::Extra(given[idx]),
+        Step::Missing(idx) => CharDiff::Missing(wanted[idx]),
+    })
+    .collect()
+}
+
+/// Aligns the sequences along their longest common subsequence.
+fn align<T>(given: &[T], wanted: &[T], eq: impl Fn(&T, &T) -> bool) -> Vec<Step> {
+    // lengths[i][j] is the length of the longest common subsequence of given[i..] and wanted[j..]
+    let mut lengths = vec![vec![0usize; wanted.len() + 1]; given.len() + 1];
+    for i in (0..given.len()).rev() {
+        for j in (0..wanted.len()).rev() {
+            lengths[i][j] = if eq(&given[i], &wanted[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut steps = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < given.len() || j < wanted.len() {
+        if i < given.len() && j < wanted.len() && eq(&given[i], &wanted[j]) {
+            steps.push(Step::Same(i));
+            i += 1;
+            j += 1;
+        } else if j == wanted.len() || (i < given.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            steps.push(Step::Extra(i));
+            i += 1;
+        } else {
+            steps.push(Step::Missing(j));
+            j += 1;
+        }
+    }
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn same(word: &str) -> WordDiff {
+        WordDiff::Same(word.to_string())
+    }
+
+    #[test]
+    fn test_diff_finds_missing_extra_moved_and_misspelled_words() {
+        let normalize = |word: &str| word.to_lowercase().replace([',', '.'], "");
+        let is_same = |a: &str, b: &str| normalize(a) == normalize(b);
+
+        assert_eq!(
+            diff("the blak cat", "The black cat.", is_same),
+            vec![
+                same("the"),
+                WordDiff::Typo(vec![
+                    CharDiff::Same('b'),
+                    CharDiff::Same('l'),
+                    CharDiff::Same('a'),
+                    CharDiff::Missing('c'),
+                    CharDiff::Same('k'),
+                ]),
+                same("cat"),
+            ]
+        );
+        assert_eq!(
+            diff("cat the black very", "the black cat", is_same),
+            vec![
+                WordDiff::Moved("cat".to_string()),
+                same("the"),
+                same("black"),
+                WordDiff::Extra("very".to_string()),
+            ]
+        );
+        assert_eq!(
+            diff("a dog", "a big cat", is_same),
+            vec![
+                same("a"),
+                WordDiff::Extra("dog".to_string()),
+                WordDiff::Missing("big".to_string()),
+                WordDiff::Missing("cat".to_string()),
+            ]
+        );
+    }
+}
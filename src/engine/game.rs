@@ -1,29 +1,142 @@
 use anyhow::Context;
 use log::{debug, trace};
+use rand::seq::SliceRandom;
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
+use super::diff::WordDiff;
+use super::hint;
+use super::language::Language;
+use super::matcher::{MatchGrade, Matcher};
+use super::scheduler::Scheduler;
+
+use crate::types::Direction;
 use crate::utils::config::Config;
-use crate::utils::database::{Database, Phrase};
+use crate::utils::database::{Database, Phrase, Review};
+
+/// A phrase practised in the current round together with its progress.
+#[derive(Debug, Clone)]
+pub struct RoundPhrase {
+    pub phrase: Phrase,
+    pub direction: Direction,
+    /// Number of answers given so far, including the correct one.
+    pub attempts: usize,
+    pub recognized: bool,
+    /// Number of hints revealed, each one lowering the grade recorded for the phrase.
+    pub hints: usize,
+    /// Time spent answering, without the time spent on the feedback screen.
+    pub time_spent: Duration,
+}
+
+impl RoundPhrase {
+    fn new((phrase, direction): (Phrase, Direction)) -> Self {
+        RoundPhrase {
+            phrase,
+            direction,
+            attempts: 0,
+            recognized: false,
+            hints: 0,
+            time_spent: Duration::ZERO,
+        }
+    }
+
+    /// Returns the latest hint revealed for the primary answer, if any.
+    pub fn hint(&self) -> Option<String> {
+        let answer = self.phrase.answers(self.direction).first()?;
+        hint::hint(answer, self.hints)
+    }
+}
+
+/// Results of a finished round: every phrase answered at least once, recognized ones first
+/// in the order they were recognized.
+#[derive(Debug, Default)]
+pub struct RoundSummary {
+    pub phrases: Vec<RoundPhrase>,
+}
+
+impl RoundSummary {
+    /// Returns the number of phrases recognized with the first answer.
+    pub fn first_try_count(&self) -> usize {
+        self.phrases
+            .iter()
+            .filter(|item| item.recognized && item.attempts == 1)
+            .count()
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.phrases.iter().map(|item| item.time_spent).sum()
+    }
+
+    /// Returns up to `count` phrases that needed the most attempts, the slowest first among
+    /// phrases with the same number of attempts.
+    pub fn hardest(&self, count: usize) -> Vec<&RoundPhrase> {
+        let mut phrases: Vec<&RoundPhrase> = self
+            .phrases
+            .iter()
+            .filter(|item| !item.recognized || item.attempts > 1)
+            .collect();
+        phrases.sort_by_key(|item| std::cmp::Reverse((item.attempts, item.time_spent)));
+        phrases.truncate(count);
+        phrases
+    }
+
+    /// Scores the round played in the timed mode and returns the score along with the
+    /// highest possible one.
+    ///
+    /// Every recognized phrase scores 100 points divided by the number of attempts and hints,
+    /// plus up to 50 points for speed, shrinking linearly with the average time per attempt
+    /// until it reaches the time limit. Phrases not recognized score nothing.
+    pub fn score(&self, time_limit: Duration) -> (usize, usize) {
+        let score: f64 = self
+            .phrases
+            .iter()
+            .filter(|item| item.recognized)
+            .map(|item| {
+                let attempts = item.attempts.max(1) as f64;
+                let average_time = item.time_spent.as_secs_f64() / attempts;
+                let speed = (1.0 - average_time / time_limit.as_secs_f64()).max(0.0);
+                (100.0 + 50.0 * speed) / (attempts + item.hints as f64)
+            })
+            .sum();
+        (score.round() as usize, self.phrases.len() * 150)
+    }
+
+    /// Returns phrases that weren't recognized with the first answer.
+    pub fn phrases_to_redrill(&self) -> Vec<(Phrase, Direction)> {
+        self.phrases
+            .iter()
+            .filter(|item| !item.recognized || item.attempts > 1)
+            .map(|item| (item.phrase.clone(), item.direction))
+            .collect()
+    }
+}
 
 /// Main heart of the application that controls the whole game state.
 ///
 /// The `Game` manages the flow of a phrase learning game, including:
-/// - Loading phrases from the database
+/// - Loading phrases from the database, picking the ones due for a review first
 /// - Tracking which phrases have been recognized/guessed correctly
 /// - Tracking attempts for unrecognized phrases
 /// - Managing the current phrase iteration
+/// - Measuring time spent answering each phrase
 /// - Persisting results back to the database
 pub struct Game {
     config: Rc<RefCell<Config>>,
     db: Database,
-    unrecognized_phrases: Vec<(Phrase, usize)>, // TODO do struct with metadata instead of tuple?
-    recognized_phrases: Vec<(Phrase, usize)>,   // TODO do struct with metadata instead of tuple?
+    scheduler: Scheduler,
+    unrecognized_phrases: Vec<RoundPhrase>,
+    recognized_phrases: Vec<RoundPhrase>,
     current_phrase_idx: Option<usize>,
+    /// When the current phrase was shown to the user.
+    phrase_shown_at: Instant,
+    /// Whether results of the current round are saved, which isn't the case for drills.
+    is_recorded: bool,
 }
 
 impl Game {
-    /// Creates a new `Game` instance and sets up connection with the database.
+    /// Creates a new `Game` instance, sets up connection with the database and restores
+    /// the scheduling state from the review history.
     ///
     /// # Arguments
     ///
@@ -36,13 +149,20 @@ impl Game {
     pub fn new(config: Rc<RefCell<Config>>) -> anyhow::Result<Self> {
         trace!("Initializing game with config: {:?}", config.borrow());
 
-        let db = Database::new(&config.borrow().db_conn_string)?;
+        let db = {
+            let config = config.borrow();
+            Database::new(&config.db_conn_string, &config.csv)?
+        };
+        let scheduler = Scheduler::new(&config.borrow().scheduler, &db.get_reviews()?);
         let game = Game {
             config,
             db,
+            scheduler,
             unrecognized_phrases: Vec::new(),
             recognized_phrases: Vec::new(),
             current_phrase_idx: None,
+            phrase_shown_at: Instant::now(),
+            is_recorded: true,
         };
 
         debug!("Game initialized");
@@ -51,83 +171,166 @@ impl Game {
 
     /// Fetches phrases for a new round from the database.
     ///
-    /// Retrieves a set number of phrases (configured in `phrases_per_round`) and initializes
-    /// the game state for a new round. All phrases start as unrecognized with 0 attempts.
-    /// The current phrase index is set to the first phrase.
+    /// Retrieves a set number of phrases (configured in `phrases_per_round`) of the configured
+    /// `deck` matching the `tag_filter`, overdue ones first, each asked in a direction allowed
+    /// by `quiz_direction`, and initializes the game state for a new round. All phrases start
+    /// as unrecognized with 0 attempts. The current phrase index is set to the first phrase.
+    ///
+    /// Phrases changed on disk since the previous round are reloaded first, so edits made
+    /// during a session show up in the next round while the current one stays unaffected.
     pub fn start_round(&mut self) -> anyhow::Result<()> {
         trace!("Starting new round, fetching phrases from database");
-        let phrases = self.db.get_phrases(self.config.borrow().phrases_per_round);
-        self.unrecognized_phrases = phrases.into_iter().map(|phrase| (phrase, 0)).collect();
+        if self.db.reload_if_changed()? {
+            self.scheduler =
+                Scheduler::new(&self.config.borrow().scheduler, &self.db.get_reviews()?);
+        }
+        let config = self.config.borrow();
+        let candidates = self
+            .db
+            .get_filtered_phrases(config.deck.as_deref(), &config.parse_tag_filter()?);
+        if candidates.is_empty() {
+            anyhow::bail!(
+                "No phrases to practise in deck {:?} matching {:?}",
+                config.deck,
+                config.active_tag_filter()
+            );
+        }
+        let phrases = self.scheduler.select(
+            &candidates,
+            config.phrases_per_round,
+            chrono::Local::now(),
+            &config.quiz_direction,
+        );
+        drop(config);
+        self.begin_round(phrases, true);
+        Ok(())
+    }
+
+    /// Starts a round practising the given phrases again, e.g. the ones missed in the
+    /// previous round.
+    ///
+    /// Drills are extra practice, so their results aren't saved and don't affect the schedule.
+    pub fn start_drill(&mut self, phrases: Vec<(Phrase, Direction)>) -> anyhow::Result<()> {
+        if phrases.is_empty() {
+            anyhow::bail!("No phrases to drill");
+        }
+        trace!("Starting drill with {} phrases", phrases.len());
+        self.begin_round(phrases, false);
+        Ok(())
+    }
+
+    fn begin_round(&mut self, phrases: Vec<(Phrase, Direction)>, is_recorded: bool) {
+        self.unrecognized_phrases = phrases.into_iter().map(RoundPhrase::new).collect();
+        self.recognized_phrases.clear();
         self.current_phrase_idx = Some(0);
+        self.phrase_shown_at = Instant::now();
+        self.is_recorded = is_recorded;
         debug!(
-            "Round started with {} phrases",
-            self.unrecognized_phrases.len()
+            "Round started with {} phrases, recorded: {}",
+            self.unrecognized_phrases.len(),
+            is_recorded
         );
-        Ok(())
     }
 
     /// Clears the game state and updates the database with round results.
     ///
-    /// Resets all internal state including recognized and unrecognized phrases,
-    /// and the current phrase index. This prepares the engine for a new round.
+    /// Every phrase that was answered at least once is saved to the database together with
+    /// its attempt count and whether it was eventually recognized, and its schedule is updated
+    /// accordingly, unless the round was a drill. Afterwards all internal state including
+    /// recognized and unrecognized phrases, and the current phrase index is reset. This prepares
+    /// the engine for a new round.
     ///
-    /// # Note
+    /// # Returns
     ///
-    /// Database update with results is planned but not yet implemented.
-    pub fn end_round(&mut self) -> anyhow::Result<()> {
-        trace!("Ending round, clearing phrases");
-        // TODO update DB with results before clearing phrases
-        self.unrecognized_phrases.clear();
-        self.recognized_phrases.clear();
+    /// * `Ok(RoundSummary)` - Results of every phrase answered during the round
+    /// * `Err` - If saving the results fails
+    pub fn end_round(&mut self) -> anyhow::Result<RoundSummary> {
+        trace!("Ending round, saving results");
+        let mut phrases = std::mem::take(&mut self.recognized_phrases);
+        phrases.extend(
+            self.unrecognized_phrases
+                .drain(..)
+                .filter(|item| item.attempts > 0),
+        );
         self.current_phrase_idx = None;
-        debug!("Round ended, phrases cleared");
-        Ok(())
+
+        if self.is_recorded {
+            let reviewed_at = chrono::Local::now();
+            let reviews: Vec<Review> = phrases
+                .iter()
+                .map(|item| Review {
+                    phrase: item.phrase.clone(),
+                    direction: item.direction,
+                    attempts: item.attempts,
+                    recognized: item.recognized,
+                    hints: item.hints,
+                    reviewed_at,
+                })
+                .collect();
+            self.db.save_reviews(&reviews)?;
+            for review in &reviews {
+                self.scheduler.review(review);
+            }
+        }
+
+        debug!("Round ended with {} phrases answered", phrases.len());
+        Ok(RoundSummary { phrases })
     }
 
-    /// Returns the original text of the current phrase.
+    /// Returns the current phrase together with the direction it's asked in.
     ///
     /// # Returns
     ///
-    /// * `Ok(&str)` - The original phrase text to be translated
+    /// * `Ok(&RoundPhrase)` - The phrase to be translated, with its accepted translations
     /// * `Err` - If current game state is invalid (e.g., no current phrase index set)
-    pub fn get_current_original(&self) -> anyhow::Result<&str> {
+    pub fn get_current_phrase(&self) -> anyhow::Result<&RoundPhrase> {
         let index = self
             .current_phrase_idx
             .context("No current phrase index set")?;
-        let phrase = &self.unrecognized_phrases[index].0;
+        let phrase = &self.unrecognized_phrases[index];
         trace!(
             "Phrase {:?} fetched (idx: {}, len: {})",
             phrase,
             index,
             self.unrecognized_phrases.len()
         );
-        Ok(phrase.0.as_str())
+        Ok(phrase)
     }
 
-    /// Returns the translation text of the current phrase.
+    /// Returns candidate answers for the current phrase in random order.
+    ///
+    /// One of the candidates is the primary accepted answer, the rest are distractors drawn
+    /// from other phrases in the database. Fewer than `count` candidates are returned when
+    /// the database is too small.
     ///
     /// # Returns
     ///
-    /// * `Ok(&str)` - The expected translation of the current phrase
+    /// * `Ok(Vec<String>)` - The shuffled candidates
     /// * `Err` - If current game state is invalid (e.g., no current phrase index set)
-    pub fn get_current_translation(&self) -> anyhow::Result<&str> {
-        let index = self
-            .current_phrase_idx
-            .context("No current phrase index set")?;
-        let phrase = &self.unrecognized_phrases[index].0;
-        trace!(
-            "Phrase {:?} fetched (idx: {}, len: {})",
-            phrase,
-            index,
-            self.unrecognized_phrases.len()
-        );
-        Ok(phrase.1.as_str())
+    pub fn get_choices(&self, count: usize) -> anyhow::Result<Vec<String>> {
+        let current = self.get_current_phrase()?;
+        let answer = current
+            .phrase
+            .answers(current.direction)
+            .first()
+            .context("Current phrase has no answers")?;
+
+        let mut choices =
+            self.db
+                .get_distractors(&current.phrase, current.direction, count.saturating_sub(1));
+        choices.push(answer.clone());
+        choices.shuffle(&mut rand::rng());
+        trace!("Choices prepared: {:?}", choices);
+        Ok(choices)
     }
 
-    /// Checks the correctness of the answer against the current phrase's translation.
+    /// Checks the correctness of the answer against the current phrase.
     ///
-    /// Compares the user's answer with the expected translation (case-insensitive,
-    /// whitespace-trimmed).
+    /// Compares the user's answer with every answer accepted in the current direction (the
+    /// translations when asked forward, the original when reversed) using the `Matcher`, which
+    /// ignores case, punctuation and whitespace, accepts alternative spellings of the answer's
+    /// language and tolerates typos up to the configured `answer_tolerance`. The time spent
+    /// answering is added to the current phrase.
     ///
     /// # Arguments
     ///
@@ -135,29 +338,129 @@ impl Game {
     ///
     /// # Returns
     ///
-    /// * `Ok(true)` - Answer matches the expected translation
-    /// * `Ok(false)` - Answer does not match
+    /// * `Ok(MatchGrade::Exact)` - Answer matches one of the accepted answers
+    /// * `Ok(MatchGrade::Close)` - Answer is within the tolerated distance, e.g. has a typo
+    /// * `Ok(MatchGrade::Wrong)` - Answer does not match
     /// * `Err` - If current game state is invalid (e.g., no current phrase index set)
-    pub fn check_phrase(&mut self, answer: &String) -> anyhow::Result<bool> {
+    pub fn check_phrase(&mut self, answer: &str) -> anyhow::Result<MatchGrade> {
         let index = self
             .current_phrase_idx
             .context("No current phrase index set")?;
-        let expected = &self.unrecognized_phrases[index].0.1;
+        let current = &mut self.unrecognized_phrases[index];
+        current.time_spent += self.phrase_shown_at.elapsed();
+        let expected = current.phrase.answers(current.direction);
 
-        // TODO implement validation logic, e.g. using Levenshtein distance
-        let result = answer.trim().to_lowercase() == expected.trim().to_lowercase();
+        let result = answer_matcher(&self.config.borrow(), current).grade_any(answer, expected);
         trace!(
-            "Check: answer: '{}', expected: '{}', result: {}",
+            "Check: answer: '{}', expected: {:?}, result: {:?}",
             answer, expected, result
         );
         Ok(result)
     }
 
+    /// Compares the answer word by word with the closest answer accepted for the current phrase.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<WordDiff>)` - Words of the answer along with the expected words it lacks
+    /// * `Err` - If current game state is invalid (e.g., no current phrase index set)
+    pub fn diff_answer(&self, answer: &str) -> anyhow::Result<Vec<WordDiff>> {
+        let current = self.get_current_phrase()?;
+        let matcher = answer_matcher(&self.config.borrow(), current);
+        let expected = matcher
+            .closest(answer, current.phrase.answers(current.direction))
+            .context("Current phrase has no answers")?;
+
+        let diff = matcher.diff(answer, expected);
+        trace!("Diff of '{}' against '{}': {:?}", answer, expected, diff);
+        Ok(diff)
+    }
+
+    /// Checks whether the picked candidate is one of the answers accepted for the current phrase.
+    ///
+    /// Unlike `check_phrase` no tolerance applies, as distractors may be close to the answer.
+    /// The time spent answering is added to the current phrase.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MatchGrade::Exact)` - The correct candidate was picked
+    /// * `Ok(MatchGrade::Wrong)` - A distractor was picked
+    /// * `Err` - If current game state is invalid (e.g., no current phrase index set)
+    pub fn check_choice(&mut self, choice: &str) -> anyhow::Result<MatchGrade> {
+        let index = self
+            .current_phrase_idx
+            .context("No current phrase index set")?;
+        let current = &mut self.unrecognized_phrases[index];
+        current.time_spent += self.phrase_shown_at.elapsed();
+        let is_correct = current
+            .phrase
+            .answers(current.direction)
+            .iter()
+            .any(|answer| answer == choice);
+
+        trace!("Check choice: '{}', correct: {}", choice, is_correct);
+        Ok(if is_correct {
+            MatchGrade::Exact
+        } else {
+            MatchGrade::Wrong
+        })
+    }
+
+    /// Reveals the next hint for the current phrase, see `hint::hint` for the hint levels.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` - The hint was revealed and will lower the grade recorded for the phrase
+    /// * `Ok(false)` - Every hint available for the phrase is revealed already
+    /// * `Err` - If current game state is invalid (e.g., no current phrase index set)
+    pub fn reveal_hint(&mut self) -> anyhow::Result<bool> {
+        let index = self
+            .current_phrase_idx
+            .context("No current phrase index set")?;
+        let current = &mut self.unrecognized_phrases[index];
+        let answer = current
+            .phrase
+            .answers(current.direction)
+            .first()
+            .context("Current phrase has no answers")?;
+        if current.hints >= hint::max_level(answer) {
+            trace!("No more hints for phrase {:?}", current.phrase);
+            return Ok(false);
+        }
+
+        current.hints += 1;
+        trace!("Hint {} revealed: {:?}", current.hints, current.hint());
+        Ok(true)
+    }
+
+    /// Returns the time left to answer the current phrase within the limit.
+    pub fn time_left(&self, time_limit: Duration) -> Duration {
+        time_limit.saturating_sub(self.phrase_shown_at.elapsed())
+    }
+
+    /// Marks the current phrase as not answered in time, which counts as a wrong answer.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MatchGrade::Timeout)` - The time spent was added to the current phrase
+    /// * `Err` - If current game state is invalid (e.g., no current phrase index set)
+    pub fn time_out(&mut self) -> anyhow::Result<MatchGrade> {
+        let index = self
+            .current_phrase_idx
+            .context("No current phrase index set")?;
+        let current = &mut self.unrecognized_phrases[index];
+        current.time_spent += self.phrase_shown_at.elapsed();
+
+        trace!("Time is up for phrase {:?}", current.phrase);
+        Ok(MatchGrade::Timeout)
+    }
+
     /// Moves the iteration to the next phrase.
     ///
-    /// If the phrase was answered correctly, it's moved from unrecognized to recognized phrases.
-    /// If not answered correctly, the attempt counter is incremented and the phrase remains in the
-    /// unrecognized pool. The iteration then advances to the next unrecognized phrase.
+    /// The attempt counter of the current phrase is incremented. If the phrase was answered
+    /// correctly, it's moved from unrecognized to recognized phrases. If not answered correctly,
+    /// the phrase remains in the unrecognized pool. The iteration then advances to the next
+    /// unrecognized phrase.
     ///
     /// # Arguments
     ///
@@ -173,18 +476,74 @@ impl Game {
             .current_phrase_idx
             .context("No current phrase index set")?;
 
+        self.phrase_shown_at = Instant::now();
+        self.unrecognized_phrases[index].attempts += 1;
         if is_correct {
-            self.recognized_phrases.push(self.unrecognized_phrases.remove(index));
+            let mut recognized = self.unrecognized_phrases.remove(index);
+            recognized.recognized = true;
+            self.recognized_phrases.push(recognized);
             if self.unrecognized_phrases.is_empty() {
                 anyhow::bail!("No more phrases available to advance to");
             } else {
                 self.current_phrase_idx = Some(index % self.unrecognized_phrases.len());
             }
         } else {
-            self.unrecognized_phrases[index].1 += 1;
             self.current_phrase_idx = Some((index + 1) % self.unrecognized_phrases.len())
         }
 
         Ok(())
     }
 }
+
+/// Returns the matcher of answers to the phrase, aware of the answer's language.
+fn answer_matcher(config: &Config, current: &RoundPhrase) -> Matcher {
+    let language = current
+        .phrase
+        .answer_language(current.direction)
+        .and_then(Language::find);
+    Matcher::new(config, language)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_phrase(original: &str, attempts: usize, recognized: bool, secs: u64) -> RoundPhrase {
+        RoundPhrase {
+            phrase: Phrase::new(original.to_string(), vec![original.to_lowercase()]),
+            direction: Direction::Forward,
+            attempts,
+            recognized,
+            hints: 0,
+            time_spent: Duration::from_secs(secs),
+        }
+    }
+
+    #[test]
+    fn test_round_summary() {
+        let summary = RoundSummary {
+            phrases: vec![
+                round_phrase("Kot", 1, true, 2),
+                round_phrase("Pies", 3, true, 9),
+                round_phrase("Ptak", 2, true, 4),
+                round_phrase("Ryba", 3, false, 5),
+            ],
+        };
+
+        assert_eq!(summary.first_try_count(), 1);
+        assert_eq!(summary.total_time(), Duration::from_secs(20));
+        let hardest: Vec<&str> = summary
+            .hardest(2)
+            .iter()
+            .map(|item| item.phrase.original.as_str())
+            .collect();
+        assert_eq!(hardest, vec!["Pies", "Ryba"]);
+        let redrill: Vec<String> = summary
+            .phrases_to_redrill()
+            .into_iter()
+            .map(|(phrase, _)| phrase.original)
+            .collect();
+        assert_eq!(redrill, vec!["Pies", "Ptak", "Ryba"]);
+        assert_eq!(summary.score(Duration::from_secs(10)), (255, 600));
+    }
+}
@@ -0,0 +1,72 @@
+/// Returns the hint of the given level for the answer, `None` past the last level.
+///
+/// The first level shows only the number of words, with a blank for each, e.g. `_ _ _`.
+/// The second one shows the first letter of every word, with a blank in place of each
+/// following letter, e.g. `t__ b____ c__`. Every next level reveals one more word, starting
+/// from the first one, but the last word is never given away. Punctuation stays visible
+/// from the second level on.
+pub fn hint(answer: &str, level: usize) -> Option<String> {
+    let words: Vec<&str> = answer.split_whitespace().collect();
+    if level == 0 || level > max_level(answer) {
+        return None;
+    }
+    if level == 1 {
+        return Some(vec!["_"; words.len()].join(" "));
+    }
+
+    let hint: Vec<String> = words
+        .iter()
+        .enumerate()
+        .map(|(idx, word)| {
+            if idx < level - 2 {
+                word.to_string()
+            } else {
+                mask(word)
+            }
+        })
+        .collect();
+    Some(hint.join(" "))
+}
+
+/// Returns the number of hints available for the answer.
+pub fn max_level(answer: &str) -> usize {
+    match answer.split_whitespace().count() {
+        0 => 0,
+        words => words + 1,
+    }
+}
+
+/// Keeps the first letter of the word and replaces the following ones with blanks.
+fn mask(word: &str) -> String {
+    let mut is_first = true;
+    word.chars()
+        .map(|c| {
+            if c.is_alphanumeric() && !std::mem::take(&mut is_first) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hint_reveals_words_progressively() {
+        let answer = "don't cry, baby";
+
+        assert_eq!(hint(answer, 0), None);
+        assert_eq!(hint(answer, 1).as_deref(), Some("_ _ _"));
+        assert_eq!(hint(answer, 2).as_deref(), Some("d__'_ c__, b___"));
+        assert_eq!(hint(answer, 3).as_deref(), Some("don't c__, b___"));
+        assert_eq!(hint(answer, 4).as_deref(), Some("don't cry, b___"));
+        assert_eq!(hint(answer, 5), None);
+        assert_eq!(hint("Cat", 1).as_deref(), Some("_"));
+        assert_eq!(hint("Cat", 2).as_deref(), Some("C__"));
+        assert_eq!(hint("Cat", 3), None);
+        assert_eq!(hint("", 1), None);
+    }
+}
@@ -0,0 +1,98 @@
+/// Rules specific to a language phrases are written in.
+///
+/// Languages are recognized by the names decks give them, e.g. the `Polski,English` header
+/// of a CSV file, which may be the English name, the native name or the ISO 639-1 code.
+#[derive(Debug, PartialEq)]
+pub struct Language {
+    pub name: &'static str,
+    /// Lowercase names the language is recognized by.
+    aliases: &'static [&'static str],
+    /// Letters missing from a US keyboard layout, shown as a hint when typing answers.
+    pub special_characters: &'static [char],
+    /// Alternative spellings accepted in answers, e.g. "ss" for "ß", as (letter, replacement).
+    spellings: &'static [(char, &'static str)],
+}
+
+impl Language {
+    /// Finds the language known under the given name, ignoring case.
+    pub fn find(name: &str) -> Option<&'static Language> {
+        let name = name.trim().to_lowercase();
+        LANGUAGES
+            .iter()
+            .find(|language| language.aliases.contains(&name.as_str()))
+    }
+
+    /// Replaces letters having an accepted alternative spelling, expecting lowercase text.
+    pub fn normalize_spelling(&self, text: &str) -> String {
+        let mut normalized = String::with_capacity(text.len());
+        for c in text.chars() {
+            match self.spellings.iter().find(|(letter, _)| *letter == c) {
+                Some((_, replacement)) => normalized.push_str(replacement),
+                None => normalized.push(c),
+            }
+        }
+        normalized
+    }
+}
+
+static LANGUAGES: &[Language] = &[
+    Language {
+        name: "English",
+        aliases: &["english", "en", "angielski"],
+        special_characters: &[],
+        spellings: &[],
+    },
+    Language {
+        name: "Polish",
+        aliases: &["polish", "polski", "pl"],
+        special_characters: &['ą', 'ć', 'ę', 'ł', 'ń', 'ó', 'ś', 'ź', 'ż'],
+        spellings: &[],
+    },
+    Language {
+        name: "German",
+        aliases: &["german", "deutsch", "de", "niemiecki"],
+        special_characters: &['ä', 'ö', 'ü', 'ß'],
+        spellings: &[('ä', "ae"), ('ö', "oe"), ('ü', "ue"), ('ß', "ss")],
+    },
+    Language {
+        name: "French",
+        aliases: &["french", "français", "francais", "fr", "francuski"],
+        special_characters: &[
+            'à', 'â', 'ç', 'é', 'è', 'ê', 'ë', 'î', 'ï', 'ô', 'ù', 'û', 'ü', 'ÿ', 'œ', 'æ',
+        ],
+        spellings: &[('œ', "oe"), ('æ', "ae")],
+    },
+    Language {
+        name: "Spanish",
+        aliases: &["spanish", "español", "espanol", "es", "hiszpański"],
+        special_characters: &['á', 'é', 'í', 'ñ', 'ó', 'ú', 'ü', '¿', '¡'],
+        spellings: &[],
+    },
+    Language {
+        name: "Italian",
+        aliases: &["italian", "italiano", "it", "włoski"],
+        special_characters: &['à', 'è', 'é', 'ì', 'ò', 'ù'],
+        spellings: &[],
+    },
+    Language {
+        name: "Portuguese",
+        aliases: &["portuguese", "português", "portugues", "pt", "portugalski"],
+        special_characters: &['á', 'â', 'ã', 'à', 'ç', 'é', 'ê', 'í', 'ó', 'ô', 'õ', 'ú'],
+        spellings: &[],
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_and_normalize_spelling() {
+        let german = Language::find(" Deutsch").unwrap();
+
+        assert_eq!(german.name, "German");
+        assert_eq!(german.normalize_spelling("straße über"), "strasse ueber");
+        assert_eq!(Language::find("PL").unwrap().name, "Polish");
+        assert_eq!(Language::find("Klingon"), None);
+    }
+}
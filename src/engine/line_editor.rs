@@ -0,0 +1,124 @@
+use crate::events::event::Edit;
+
+/// Text typed into an input box, with the position of the cursor in it.
+///
+/// The cursor is counted in characters, from 0 before the first one up to the length of the
+/// text after the last one.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LineEditor {
+    text: String,
+    cursor: usize,
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        LineEditor::default()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Replaces the text, placing the cursor after it.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = text.chars().count();
+    }
+
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+
+    /// Returns the text, `None` if nothing was typed, and clears the editor.
+    pub fn take(&mut self) -> Option<String> {
+        self.cursor = 0;
+        Some(std::mem::take(&mut self.text)).filter(|text| !text.is_empty())
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let idx = self.byte_index(self.cursor);
+        self.text.insert(idx, c);
+        self.cursor += 1;
+    }
+
+    pub fn edit(&mut self, edit: Edit) {
+        let len = self.text.chars().count();
+        match edit {
+            Edit::RemoveCharacter if self.cursor > 0 => {
+                self.remove(self.cursor - 1, self.cursor);
+            }
+            Edit::RemoveNextCharacter if self.cursor < len => {
+                self.remove(self.cursor, self.cursor + 1);
+            }
+            Edit::RemoveWord => {
+                let chars: Vec<char> = self.text.chars().take(self.cursor).collect();
+                let word_end = chars
+                    .iter()
+                    .rposition(|c| !c.is_whitespace())
+                    .map_or(0, |idx| idx + 1);
+                let word_start = chars[..word_end]
+                    .iter()
+                    .rposition(|c| c.is_whitespace())
+                    .map_or(0, |idx| idx + 1);
+                self.remove(word_start, self.cursor);
+            }
+            Edit::MoveLeft => self.cursor = self.cursor.saturating_sub(1),
+            Edit::MoveRight => self.cursor = (self.cursor + 1).min(len),
+            Edit::MoveToStart => self.cursor = 0,
+            Edit::MoveToEnd => self.cursor = len,
+            Edit::RemoveCharacter | Edit::RemoveNextCharacter => (),
+        }
+    }
+
+    /// Removes the characters between the given positions, leaving the cursor at the start.
+    fn remove(&mut self, start: usize, end: usize) {
+        let range = self.byte_index(start)..self.byte_index(end);
+        self.text.replace_range(range, "");
+        self.cursor = start;
+    }
+
+    fn byte_index(&self, position: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(position)
+            .map_or(self.text.len(), |(idx, _)| idx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edits_at_the_cursor() {
+        let mut editor = LineEditor::new();
+        editor.set_text("zażółć jaźń");
+        editor.edit(Edit::MoveToStart);
+        editor.edit(Edit::MoveRight);
+        editor.insert('X');
+        editor.edit(Edit::RemoveNextCharacter);
+        assert_eq!((editor.text(), editor.cursor()), ("zXżółć jaźń", 2));
+
+        editor.edit(Edit::MoveToEnd);
+        editor.edit(Edit::MoveLeft);
+        editor.edit(Edit::RemoveCharacter);
+        assert_eq!((editor.text(), editor.cursor()), ("zXżółć jań", 9));
+
+        editor.edit(Edit::RemoveWord);
+        assert_eq!((editor.text(), editor.cursor()), ("zXżółć ń", 7));
+        editor.edit(Edit::RemoveWord);
+        assert_eq!((editor.text(), editor.cursor()), ("ń", 0));
+        editor.edit(Edit::RemoveCharacter);
+        assert_eq!(editor.take(), Some("ń".to_string()));
+        assert_eq!(editor.take(), None);
+    }
+}
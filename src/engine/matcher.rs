@@ -0,0 +1,295 @@
+use log::trace;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+use super::diff::{self, WordDiff};
+use super::language::Language;
+
+use crate::utils::config::Config;
+
+/// Result of comparing an answer with the expected translation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatchGrade {
+    /// Same text once case, punctuation and whitespace are ignored.
+    Exact,
+    /// Within the configured edit distance, e.g. a typo.
+    Close,
+    Wrong,
+    /// No answer given before the countdown ran out in the timed mode.
+    Timeout,
+}
+
+impl MatchGrade {
+    pub fn is_correct(&self) -> bool {
+        matches!(self, MatchGrade::Exact | MatchGrade::Close)
+    }
+}
+
+/// Compares answers with expected translations in a forgiving way.
+///
+/// Both texts are normalized to NFC, lowercased, stripped of punctuation and have their
+/// whitespace collapsed. Alternative spellings of the answer's language are accepted, e.g.
+/// "ss" for "ß" in German, and optionally diacritics are folded too, so "ą" matches "a".
+/// Answers that are still different are accepted as close if the Levenshtein distance
+/// relative to the length of the expected text doesn't exceed the tolerance.
+pub struct Matcher {
+    tolerance: f64,
+    fold_diacritics: bool,
+    /// Language of the answers, if known.
+    language: Option<&'static Language>,
+}
+
+impl Matcher {
+    pub fn new(config: &Config, language: Option<&'static Language>) -> Self {
+        Matcher {
+            tolerance: config.answer_tolerance,
+            fold_diacritics: config.fold_diacritics,
+            language,
+        }
+    }
+
+    pub fn grade(&self, answer: &str, expected: &str) -> MatchGrade {
+        let ratio = self.difference(answer, expected);
+        let grade = if ratio == 0.0 {
+            MatchGrade::Exact
+        } else if ratio <= self.tolerance {
+            MatchGrade::Close
+        } else {
+            MatchGrade::Wrong
+        };
+
+        trace!(
+            "Graded answer '{}' against '{}' (ratio {:.2}, tolerance {:.2}): {:?}",
+            answer, expected, ratio, self.tolerance, grade
+        );
+        grade
+    }
+
+    /// Grades the answer against every accepted translation and returns the best grade.
+    pub fn grade_any(&self, answer: &str, accepted: &[String]) -> MatchGrade {
+        let mut best = MatchGrade::Wrong;
+        for expected in accepted {
+            match self.grade(answer, expected) {
+                MatchGrade::Exact => return MatchGrade::Exact,
+                MatchGrade::Close => best = MatchGrade::Close,
+                MatchGrade::Wrong | MatchGrade::Timeout => (),
+            }
+        }
+        best
+    }
+
+    /// Returns the accepted translation closest to the answer, `None` if there are none.
+    pub fn closest<'a>(&self, answer: &str, accepted: &'a [String]) -> Option<&'a str> {
+        accepted
+            .iter()
+            .min_by(|a, b| {
+                self.difference(answer, a)
+                    .total_cmp(&self.difference(answer, b))
+            })
+            .map(String::as_str)
+    }
+
+    /// Compares the answer with the expected translation word by word, telling words apart
+    /// the same way answers are graded.
+    pub fn diff(&self, answer: &str, expected: &str) -> Vec<WordDiff> {
+        diff::diff(answer, expected, |a, b| self.difference(a, b) == 0.0)
+    }
+
+    /// Returns the Levenshtein distance between the normalized texts relative to the length
+    /// of the expected one, the smallest of the distances between their forms.
+    fn difference(&self, answer: &str, expected: &str) -> f64 {
+        self.normalize(answer)
+            .iter()
+            .zip(self.normalize(expected))
+            .map(|(answer, expected)| {
+                let length = expected.chars().count().max(1);
+                levenshtein(answer, &expected) as f64 / length as f64
+            })
+            .fold(f64::INFINITY, f64::min)
+    }
+
+    /// Returns the forms the text is compared in: spelled the way of the answer's language
+    /// and, when diacritics are folded, also with the original letters folded. Both are
+    /// needed as e.g. "über" becomes "ueber" in the first form and "uber" in the second.
+    fn normalize(&self, text: &str) -> Vec<String> {
+        let text: String = text.nfc().flat_map(char::to_lowercase).collect();
+        let mut forms = Vec::new();
+        if let Some(language) = self.language {
+            forms.push(language.normalize_spelling(&text));
+        }
+        if forms.is_empty() || self.fold_diacritics {
+            forms.push(text);
+        }
+
+        forms
+            .into_iter()
+            .map(|form| {
+                let form = if self.fold_diacritics {
+                    fold_diacritics(&form)
+                } else {
+                    form
+                };
+                form.chars()
+                    .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+                    .collect::<String>()
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect()
+    }
+}
+
+/// Strips combining marks after canonical decomposition and replaces letters that don't
+/// decompose, e.g. "ł" or "ø", with their closest ASCII counterparts.
+fn fold_diacritics(text: &str) -> String {
+    text.nfd()
+        .filter(|c| !is_combining_mark(*c))
+        .flat_map(|c| {
+            let folded = match c {
+                'ł' => "l",
+                'đ' => "d",
+                'ø' => "o",
+                'ß' => "ss",
+                'æ' => "ae",
+                'œ' => "oe",
+                _ => return vec![c],
+            };
+            folded.chars().collect()
+        })
+        .collect()
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(tolerance: f64, fold_diacritics: bool) -> Matcher {
+        Matcher {
+            tolerance,
+            fold_diacritics,
+            language: None,
+        }
+    }
+
+    #[test]
+    fn test_grade_ignores_case_punctuation_and_whitespace() {
+        let matcher = matcher(0.0, false);
+
+        assert_eq!(
+            matcher.grade("  it was BOUND to happen!", "It was bound to happen"),
+            MatchGrade::Exact
+        );
+        assert_eq!(
+            matcher.grade("It was bound, to happen", "It was bound to happen"),
+            MatchGrade::Exact
+        );
+    }
+
+    #[test]
+    fn test_grade_normalizes_unicode() {
+        let decomposed = "Zaz\u{0307}o\u{0301}łc\u{0301}";
+
+        assert_eq!(
+            matcher(0.0, false).grade(decomposed, "Zażółć"),
+            MatchGrade::Exact
+        );
+        assert_eq!(
+            matcher(0.0, false).grade("Zazolc", "Zażółć"),
+            MatchGrade::Wrong
+        );
+        assert_eq!(
+            matcher(0.0, true).grade("Zazolc", "Zażółć"),
+            MatchGrade::Exact
+        );
+    }
+
+    #[test]
+    fn test_grade_accepts_alternative_spellings_of_language() {
+        let german = Matcher {
+            language: Language::find("German"),
+            ..matcher(0.0, false)
+        };
+
+        assert_eq!(german.grade("Strasse", "Straße"), MatchGrade::Exact);
+        assert_eq!(german.grade("uber", "über"), MatchGrade::Wrong);
+        assert_eq!(
+            matcher(0.0, false).grade("Strasse", "Straße"),
+            MatchGrade::Wrong
+        );
+    }
+
+    #[test]
+    fn test_grade_accepts_spellings_and_folded_diacritics_together() {
+        let german = Matcher {
+            language: Language::find("German"),
+            ..matcher(0.0, true)
+        };
+
+        assert_eq!(german.grade("uber", "über"), MatchGrade::Exact);
+        assert_eq!(german.grade("ueber", "über"), MatchGrade::Exact);
+        assert_eq!(german.grade("Strasse", "Straße"), MatchGrade::Exact);
+        assert_eq!(german.grade("Kafer", "Käfer"), MatchGrade::Exact);
+        assert!(
+            german
+                .diff("uber alles", "über alles")
+                .iter()
+                .all(|word| matches!(word, WordDiff::Same(_)))
+        );
+    }
+
+    #[test]
+    fn test_grade_accepts_typos_within_tolerance() {
+        let matcher = matcher(0.1, false);
+
+        assert_eq!(
+            matcher.grade("Forecasts are comming true", "Forecasts are coming true"),
+            MatchGrade::Close
+        );
+        assert_eq!(
+            matcher.grade("Forecasts are true", "Forecasts are coming true"),
+            MatchGrade::Wrong
+        );
+    }
+
+    #[test]
+    fn test_grade_any_returns_best_grade() {
+        let matcher = matcher(0.1, false);
+        let accepted = vec!["Issue an appeal".to_string(), "Make an appeal".to_string()];
+
+        assert_eq!(
+            matcher.grade_any("make an appeal", &accepted),
+            MatchGrade::Exact
+        );
+        assert_eq!(
+            matcher.grade_any("Isue an appeal", &accepted),
+            MatchGrade::Close
+        );
+        assert_eq!(matcher.grade_any("Appeal", &accepted), MatchGrade::Wrong);
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("ąę", "ąę"), 0);
+    }
+}
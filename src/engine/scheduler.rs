@@ -0,0 +1,282 @@
+mod fsrs;
+mod sm2;
+
+use chrono::{DateTime, Duration, Local};
+use log::{debug, trace};
+use rand::seq::SliceRandom;
+use std::collections::{HashMap, HashSet};
+
+use crate::types::{Direction, QuizDirection, SchedulerAlgorithm};
+use crate::utils::database::{Phrase, PhraseId, Phrases, Review};
+
+use fsrs::Fsrs;
+use sm2::Sm2;
+
+/// How well a phrase was remembered during a round.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Grade {
+    Again,
+    Hard,
+    Good,
+}
+
+/// Every hint revealed lowers the grade as much as another attempt would.
+impl From<&Review> for Grade {
+    fn from(review: &Review) -> Self {
+        match (review.recognized, review.attempts + review.hints) {
+            (true, 1) => Grade::Good,
+            (true, 2) => Grade::Hard,
+            _ => Grade::Again,
+        }
+    }
+}
+
+/// Scheduling data of a single phrase.
+///
+/// `ease` is the algorithm's ease parameter: the easiness factor for SM-2 and the
+/// difficulty (1-10) for FSRS. `stability` is only used by FSRS.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CardState {
+    pub ease: f64,
+    pub stability: f64,
+    pub interval_days: f64,
+    pub repetitions: u32,
+    pub last_review: DateTime<Local>,
+    pub due: DateTime<Local>,
+}
+
+/// Spaced-repetition algorithm computing the next state of a phrase after a review.
+pub trait SchedulingAlgorithm {
+    /// Returns the state of a phrase after it has been graded at `reviewed_at`.
+    /// `state` is `None` for phrases reviewed for the first time.
+    fn review(
+        &self,
+        state: Option<&CardState>,
+        grade: Grade,
+        reviewed_at: DateTime<Local>,
+    ) -> CardState;
+}
+
+/// Keeps the scheduling state of every reviewed phrase and decides what to practise next.
+///
+/// Each direction of a phrase is scheduled separately, as recognizing a phrase and recalling
+/// it are different skills.
+///
+/// The state isn't persisted on its own - it's rebuilt by replaying the review history,
+/// so switching algorithms takes effect on the next start.
+pub struct Scheduler {
+    algorithm: Box<dyn SchedulingAlgorithm>,
+    cards: HashMap<(PhraseId, Direction), CardState>,
+}
+
+impl Scheduler {
+    pub fn new(algorithm: &SchedulerAlgorithm, history: &[Review]) -> Self {
+        let algorithm: Box<dyn SchedulingAlgorithm> = match algorithm {
+            SchedulerAlgorithm::Sm2 => Box::new(Sm2),
+            SchedulerAlgorithm::Fsrs => Box::new(Fsrs::default()),
+        };
+        let mut scheduler = Scheduler {
+            algorithm,
+            cards: HashMap::new(),
+        };
+        for review in history {
+            scheduler.review(review);
+        }
+
+        debug!(
+            "Scheduler initialized with {} cards from {} reviews",
+            scheduler.cards.len(),
+            history.len()
+        );
+        scheduler
+    }
+
+    /// Updates the state of the reviewed phrase.
+    pub fn review(&mut self, review: &Review) {
+        let key = (review.phrase.id, review.direction);
+        let grade = Grade::from(review);
+        let state = self
+            .algorithm
+            .review(self.cards.get(&key), grade, review.reviewed_at);
+        trace!("Phrase {:?} graded {:?}: {:?}", key, grade, state);
+        self.cards.insert(key, state);
+    }
+
+    /// Returns the scheduling state of a phrase in the given direction, `None` if it was
+    /// never reviewed in it.
+    pub fn card(&self, id: PhraseId, direction: Direction) -> Option<&CardState> {
+        self.cards.get(&(id, direction))
+    }
+
+    /// Counts phrase directions whose review is due at `now`.
+    pub fn count_due(&self, now: DateTime<Local>) -> usize {
+        self.cards.values().filter(|state| state.due <= now).count()
+    }
+
+    /// Picks up to `limit` phrases to practise in the allowed directions.
+    ///
+    /// Overdue phrases come first (the most overdue ones at the top), then phrases never
+    /// reviewed before in random order, then the ones due soonest. A phrase is picked at most
+    /// once per round, in the direction that is due the most.
+    pub fn select(
+        &self,
+        phrases: &Phrases,
+        limit: usize,
+        now: DateTime<Local>,
+        quiz_direction: &QuizDirection,
+    ) -> Vec<(Phrase, Direction)> {
+        let mut overdue = Vec::new();
+        let mut new = Vec::new();
+        let mut upcoming = Vec::new();
+        for phrase in phrases {
+            for direction in quiz_direction.directions() {
+                let card = (phrase, *direction);
+                match self.cards.get(&(phrase.id, *direction)) {
+                    Some(state) if state.due <= now => overdue.push((state.due, card)),
+                    Some(state) => upcoming.push((state.due, card)),
+                    None => new.push(card),
+                }
+            }
+        }
+        overdue.sort_by_key(|(due, _)| *due);
+        upcoming.sort_by_key(|(due, _)| *due);
+        new.shuffle(&mut rand::rng());
+        trace!(
+            "Selecting from {} overdue, {} new and {} upcoming cards",
+            overdue.len(),
+            new.len(),
+            upcoming.len()
+        );
+
+        let mut selected_ids = HashSet::new();
+        overdue
+            .into_iter()
+            .map(|(_, card)| card)
+            .chain(new)
+            .chain(upcoming.into_iter().map(|(_, card)| card))
+            .filter(|(phrase, _)| selected_ids.insert(phrase.id))
+            .take(limit)
+            .map(|(phrase, direction)| (phrase.clone(), direction))
+            .collect()
+    }
+}
+
+fn days(interval_days: f64) -> Duration {
+    Duration::seconds((interval_days * 86_400.0).round() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KOT: PhraseId = 1;
+    const PIES: PhraseId = 2;
+    const PTAK: PhraseId = 3;
+
+    fn phrase(id: PhraseId) -> Phrase {
+        Phrase {
+            id,
+            ..Default::default()
+        }
+    }
+
+    fn review(id: PhraseId, attempts: usize, reviewed_at: DateTime<Local>) -> Review {
+        Review {
+            phrase: phrase(id),
+            direction: Direction::Forward,
+            attempts,
+            recognized: true,
+            hints: 0,
+            reviewed_at,
+        }
+    }
+
+    #[test]
+    fn test_sm2_intervals_grow_on_good_answers() {
+        let now = Local::now();
+        let history: Vec<Review> = (0..3)
+            .map(|day| review(KOT, 1, now + Duration::days(day)))
+            .collect();
+
+        let intervals: Vec<f64> = (1..=3)
+            .map(|count| {
+                let scheduler = Scheduler::new(&SchedulerAlgorithm::Sm2, &history[..count]);
+                scheduler
+                    .cards
+                    .get(&(KOT, Direction::Forward))
+                    .unwrap()
+                    .interval_days
+            })
+            .collect();
+
+        assert_eq!(intervals[0], 1.0);
+        assert_eq!(intervals[1], 6.0);
+        assert!(intervals[2] > intervals[1]);
+    }
+
+    #[test]
+    fn test_hints_lower_the_grade() {
+        let mut hinted = review(KOT, 1, Local::now());
+        assert_eq!(Grade::from(&hinted), Grade::Good);
+        hinted.hints = 1;
+        assert_eq!(Grade::from(&hinted), Grade::Hard);
+        hinted.hints = 2;
+        assert_eq!(Grade::from(&hinted), Grade::Again);
+    }
+
+    #[test]
+    fn test_fsrs_failed_review_shortens_interval() {
+        let now = Local::now();
+        let good = vec![review(KOT, 1, now), review(KOT, 1, now + Duration::days(3))];
+        let mut failed = good.clone();
+        failed.push(review(KOT, 3, now + Duration::days(10)));
+
+        let scheduler = Scheduler::new(&SchedulerAlgorithm::Fsrs, &good);
+        let good_state = scheduler
+            .cards
+            .get(&(KOT, Direction::Forward))
+            .unwrap()
+            .clone();
+        let scheduler = Scheduler::new(&SchedulerAlgorithm::Fsrs, &failed);
+        let failed_state = scheduler.cards.get(&(KOT, Direction::Forward)).unwrap();
+
+        assert!(failed_state.stability < good_state.stability);
+        assert!(failed_state.ease > good_state.ease);
+    }
+
+    #[test]
+    fn test_select_puts_overdue_phrases_first() {
+        let now = Local::now();
+        let history = vec![
+            review(KOT, 1, now),
+            review(PTAK, 1, now - Duration::days(10)),
+        ];
+        let scheduler = Scheduler::new(&SchedulerAlgorithm::Sm2, &history);
+        let phrases = vec![phrase(KOT), phrase(PIES), phrase(PTAK)];
+
+        let selected = scheduler.select(&phrases, 3, now, &QuizDirection::Forward);
+
+        let forward = |id| (phrase(id), Direction::Forward);
+        assert_eq!(selected, vec![forward(PTAK), forward(PIES), forward(KOT)]);
+        assert_eq!(
+            scheduler.select(&phrases, 1, now, &QuizDirection::Forward),
+            vec![forward(PTAK)]
+        );
+    }
+
+    #[test]
+    fn test_select_tracks_directions_separately() {
+        let now = Local::now();
+        let history = vec![review(KOT, 1, now - Duration::days(10))];
+        let scheduler = Scheduler::new(&SchedulerAlgorithm::Sm2, &history);
+        let phrases = vec![phrase(KOT)];
+
+        let selected = scheduler.select(&phrases, 2, now, &QuizDirection::Random);
+
+        assert_eq!(selected, vec![(phrase(KOT), Direction::Forward)]);
+        assert_eq!(
+            scheduler.select(&phrases, 2, now, &QuizDirection::Reverse),
+            vec![(phrase(KOT), Direction::Reverse)]
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, Local};
+
+use super::{CardState, Grade, SchedulingAlgorithm, days};
+
+const DECAY: f64 = -0.5;
+const FACTOR: f64 = 19.0 / 81.0;
+
+/// Free Spaced Repetition Scheduler (FSRS v4.5) with the default parameters.
+pub struct Fsrs {
+    weights: [f64; 17],
+    desired_retention: f64,
+}
+
+impl Default for Fsrs {
+    fn default() -> Self {
+        Fsrs {
+            weights: [
+                0.4872, 1.4003, 3.7145, 13.8206, 5.1618, 1.2298, 0.8975, 0.031, 1.6474, 0.1367,
+                1.0461, 2.1072, 0.0793, 0.3246, 1.587, 0.2272, 2.8755,
+            ],
+            desired_retention: 0.9,
+        }
+    }
+}
+
+impl Fsrs {
+    /// FSRS rates answers from 1 (again) to 4 (easy).
+    fn rating(grade: Grade) -> f64 {
+        match grade {
+            Grade::Again => 1.0,
+            Grade::Hard => 2.0,
+            Grade::Good => 3.0,
+        }
+    }
+
+    fn initial_stability(&self, rating: f64) -> f64 {
+        self.weights[rating as usize - 1].max(0.1)
+    }
+
+    fn initial_difficulty(&self, rating: f64) -> f64 {
+        (self.weights[4] - (rating - 3.0) * self.weights[5]).clamp(1.0, 10.0)
+    }
+
+    fn retrievability(elapsed_days: f64, stability: f64) -> f64 {
+        (1.0 + FACTOR * elapsed_days / stability).powf(DECAY)
+    }
+
+    fn next_difficulty(&self, difficulty: f64, rating: f64) -> f64 {
+        let difficulty = difficulty - self.weights[6] * (rating - 3.0);
+        let reverted =
+            self.weights[7] * self.initial_difficulty(3.0) + (1.0 - self.weights[7]) * difficulty;
+        reverted.clamp(1.0, 10.0)
+    }
+
+    fn next_stability(
+        &self,
+        difficulty: f64,
+        stability: f64,
+        retrievability: f64,
+        rating: f64,
+    ) -> f64 {
+        let w = &self.weights;
+        if rating == 1.0 {
+            w[11]
+                * difficulty.powf(-w[12])
+                * ((stability + 1.0).powf(w[13]) - 1.0)
+                * (w[14] * (1.0 - retrievability)).exp()
+        } else {
+            let hard_penalty = if rating == 2.0 { w[15] } else { 1.0 };
+            stability
+                * (w[8].exp()
+                    * (11.0 - difficulty)
+                    * stability.powf(-w[9])
+                    * ((w[10] * (1.0 - retrievability)).exp() - 1.0)
+                    * hard_penalty
+                    + 1.0)
+        }
+    }
+
+    fn interval(&self, stability: f64) -> f64 {
+        (stability / FACTOR * (self.desired_retention.powf(1.0 / DECAY) - 1.0))
+            .round()
+            .max(1.0)
+    }
+}
+
+impl SchedulingAlgorithm for Fsrs {
+    fn review(
+        &self,
+        state: Option<&CardState>,
+        grade: Grade,
+        reviewed_at: DateTime<Local>,
+    ) -> CardState {
+        let rating = Fsrs::rating(grade);
+        let (difficulty, stability, repetitions) = match state {
+            None => (
+                self.initial_difficulty(rating),
+                self.initial_stability(rating),
+                0,
+            ),
+            Some(state) => {
+                let elapsed_days =
+                    ((reviewed_at - state.last_review).num_seconds() as f64 / 86_400.0).max(0.0);
+                let retrievability = Fsrs::retrievability(elapsed_days, state.stability);
+                (
+                    self.next_difficulty(state.ease, rating),
+                    self.next_stability(state.ease, state.stability, retrievability, rating),
+                    state.repetitions,
+                )
+            }
+        };
+        let interval_days = self.interval(stability);
+        let repetitions = if grade == Grade::Again {
+            0
+        } else {
+            repetitions + 1
+        };
+
+        CardState {
+            ease: difficulty,
+            stability,
+            interval_days,
+            repetitions,
+            last_review: reviewed_at,
+            due: reviewed_at + days(interval_days),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use chrono::{DateTime, Local};
+
+use super::{CardState, Grade, SchedulingAlgorithm, days};
+
+const INITIAL_EASE: f64 = 2.5;
+const MINIMAL_EASE: f64 = 1.3;
+
+/// Classic SuperMemo-2 algorithm.
+pub struct Sm2;
+
+impl Sm2 {
+    fn quality(grade: Grade) -> f64 {
+        match grade {
+            Grade::Again => 1.0,
+            Grade::Hard => 3.0,
+            Grade::Good => 4.0,
+        }
+    }
+}
+
+impl SchedulingAlgorithm for Sm2 {
+    fn review(
+        &self,
+        state: Option<&CardState>,
+        grade: Grade,
+        reviewed_at: DateTime<Local>,
+    ) -> CardState {
+        let (ease, interval_days, repetitions) = state
+            .map(|state| (state.ease, state.interval_days, state.repetitions))
+            .unwrap_or((INITIAL_EASE, 0.0, 0));
+
+        let quality = Sm2::quality(grade);
+        let ease =
+            (ease + 0.1 - (5.0 - quality) * (0.08 + (5.0 - quality) * 0.02)).max(MINIMAL_EASE);
+        let (interval_days, repetitions) = if quality < 3.0 {
+            (1.0, 0)
+        } else {
+            let interval_days = match repetitions {
+                0 => 1.0,
+                1 => 6.0,
+                _ => (interval_days * ease).round(),
+            };
+            (interval_days, repetitions + 1)
+        };
+
+        CardState {
+            ease,
+            stability: interval_days,
+            interval_days,
+            repetitions,
+            last_review: reviewed_at,
+            due: reviewed_at + days(interval_days),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use chrono::{DateTime, Days, Local, NaiveDate};
+use log::debug;
+use std::collections::{BTreeSet, HashMap};
+
+use super::scheduler::Scheduler;
+
+use crate::types::Direction;
+use crate::utils::database::{Phrases, Review};
+
+/// Number of past days covered by `Statistics::daily`.
+pub const HISTORY_DAYS: usize = 30;
+/// Number of days, starting today, covered by `Statistics::upcoming`.
+pub const UPCOMING_DAYS: usize = 7;
+
+/// How well a phrase is known in a direction, based on its current review interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MasteryLevel {
+    /// Never reviewed.
+    New,
+    /// Interval shorter than a week.
+    Learning,
+    /// Interval shorter than three weeks.
+    Young,
+    Mature,
+}
+
+impl MasteryLevel {
+    pub const ALL: [MasteryLevel; 4] = [
+        MasteryLevel::New,
+        MasteryLevel::Learning,
+        MasteryLevel::Young,
+        MasteryLevel::Mature,
+    ];
+
+    fn from_interval(interval_days: Option<f64>) -> Self {
+        match interval_days {
+            None => MasteryLevel::New,
+            Some(days) if days < 7.0 => MasteryLevel::Learning,
+            Some(days) if days < 21.0 => MasteryLevel::Young,
+            Some(_) => MasteryLevel::Mature,
+        }
+    }
+}
+
+/// Reviews done on a single day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayStats {
+    pub date: NaiveDate,
+    pub reviews: usize,
+    /// Reviews recognized with the first answer.
+    pub first_try: usize,
+}
+
+impl DayStats {
+    /// Returns the share of reviews recognized with the first answer, `None` on days without
+    /// reviews.
+    pub fn accuracy(&self) -> Option<f64> {
+        (self.reviews > 0).then(|| self.first_try as f64 / self.reviews as f64)
+    }
+}
+
+/// Long-term progress computed from the review history.
+#[derive(Debug)]
+pub struct Statistics {
+    /// Reviews of the last `HISTORY_DAYS` days, the oldest first, including idle days.
+    pub daily: Vec<DayStats>,
+    /// Consecutive days with reviews up to today, or yesterday if nothing was reviewed today yet.
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    /// Number of phrases at each level, counted separately for every quizzed direction.
+    pub mastery: HashMap<MasteryLevel, usize>,
+    /// Reviews due on each of the next `UPCOMING_DAYS` days; today includes overdue ones.
+    pub upcoming: Vec<(NaiveDate, usize)>,
+    pub total_reviews: usize,
+}
+
+impl Statistics {
+    pub fn new(
+        phrases: &Phrases,
+        history: &[Review],
+        scheduler: &Scheduler,
+        directions: &[Direction],
+        now: DateTime<Local>,
+    ) -> Self {
+        let today = now.date_naive();
+        let review_days: BTreeSet<NaiveDate> = history
+            .iter()
+            .map(|review| review.reviewed_at.date_naive())
+            .collect();
+
+        let daily = (0..HISTORY_DAYS)
+            .rev()
+            .map(|days_ago| {
+                let date = today - Days::new(days_ago as u64);
+                let reviews = history
+                    .iter()
+                    .filter(|review| review.reviewed_at.date_naive() == date);
+                DayStats {
+                    date,
+                    reviews: reviews.clone().count(),
+                    first_try: reviews
+                        .filter(|review| review.recognized && review.attempts == 1)
+                        .count(),
+                }
+            })
+            .collect();
+
+        let mut mastery = HashMap::new();
+        let mut upcoming: Vec<(NaiveDate, usize)> = (0..UPCOMING_DAYS)
+            .map(|days| (today + Days::new(days as u64), 0))
+            .collect();
+        for phrase in phrases {
+            for direction in directions {
+                let state = scheduler.card(phrase.id, *direction);
+                let level = MasteryLevel::from_interval(state.map(|state| state.interval_days));
+                *mastery.entry(level).or_insert(0) += 1;
+
+                let Some(state) = state else { continue };
+                let due = state.due.date_naive().max(today);
+                if let Some((_, count)) = upcoming.iter_mut().find(|(date, _)| *date == due) {
+                    *count += 1;
+                }
+            }
+        }
+
+        let statistics = Statistics {
+            daily,
+            current_streak: current_streak(&review_days, today),
+            longest_streak: longest_streak(&review_days),
+            mastery,
+            upcoming,
+            total_reviews: history.len(),
+        };
+        debug!("Statistics computed: {:?}", statistics);
+        statistics
+    }
+}
+
+fn current_streak(review_days: &BTreeSet<NaiveDate>, today: NaiveDate) -> usize {
+    let mut day = if review_days.contains(&today) {
+        today
+    } else {
+        today - Days::new(1)
+    };
+    let mut streak = 0;
+    while review_days.contains(&day) {
+        streak += 1;
+        day = day - Days::new(1);
+    }
+    streak
+}
+
+fn longest_streak(review_days: &BTreeSet<NaiveDate>) -> usize {
+    let mut longest = 0;
+    let mut streak = 0;
+    let mut previous: Option<NaiveDate> = None;
+    for day in review_days {
+        streak = match previous {
+            Some(previous) if previous + Days::new(1) == *day => streak + 1,
+            _ => 1,
+        };
+        longest = longest.max(streak);
+        previous = Some(*day);
+    }
+    longest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    use crate::types::SchedulerAlgorithm;
+    use crate::utils::database::Phrase;
+
+    fn review(id: i64, attempts: usize, reviewed_at: DateTime<Local>) -> Review {
+        Review {
+            phrase: Phrase {
+                id,
+                ..Default::default()
+            },
+            direction: Direction::Forward,
+            attempts,
+            recognized: true,
+            hints: 0,
+            reviewed_at,
+        }
+    }
+
+    #[test]
+    fn test_statistics_from_history() {
+        let now = Local::now();
+        let history: Vec<Review> = [10, 9, 2, 1]
+            .into_iter()
+            .map(|days_ago| review(1, 1, now - Duration::days(days_ago)))
+            .chain([review(2, 2, now - Duration::days(1))])
+            .collect();
+        let phrases: Phrases = (1..=3)
+            .map(|id| Phrase {
+                id,
+                ..Default::default()
+            })
+            .collect();
+        let scheduler = Scheduler::new(&SchedulerAlgorithm::Sm2, &history);
+
+        let statistics =
+            Statistics::new(&phrases, &history, &scheduler, &[Direction::Forward], now);
+
+        assert_eq!(statistics.current_streak, 2);
+        assert_eq!(statistics.longest_streak, 2);
+        assert_eq!(statistics.daily.len(), HISTORY_DAYS);
+        let yesterday = &statistics.daily[HISTORY_DAYS - 2];
+        assert_eq!((yesterday.reviews, yesterday.first_try), (2, 1));
+        assert_eq!(statistics.mastery[&MasteryLevel::New], 1);
+        assert_eq!(statistics.mastery[&MasteryLevel::Mature], 1);
+        assert_eq!(statistics.upcoming[0].1, 1);
+    }
+}
@@ -3,6 +3,30 @@ pub enum Event {
     Back,
     Enter,
     Quit,
-    RemoveCharacter,
     Character(char),
+    /// Changes the typed text other than by adding a character, see `LineEditor`.
+    Edit(Edit),
+    /// Asks for a hint to the current phrase, sent on F1 or Ctrl-T.
+    Hint,
+    /// The terminal was resized, so the screen has to be laid out again.
+    Resize,
+    /// Time passed without any key being pressed, sent only to states asking for ticks.
+    Tick,
+}
+
+/// Editing keys of the input box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Edit {
+    /// Removes the character before the cursor, sent on Backspace.
+    RemoveCharacter,
+    /// Removes the character under the cursor, sent on Delete.
+    RemoveNextCharacter,
+    /// Removes the word before the cursor, sent on Ctrl-W or Alt-Backspace.
+    RemoveWord,
+    MoveLeft,
+    MoveRight,
+    /// Moves the cursor to the start of the text, sent on Home or Ctrl-A.
+    MoveToStart,
+    /// Moves the cursor to the end of the text, sent on End or Ctrl-E.
+    MoveToEnd,
 }
@@ -1,8 +1,9 @@
 use log::trace;
+use std::time::{Duration, Instant};
 
 use crossterm::event as ct_event;
 
-use super::event::Event;
+use super::event::{Edit, Event};
 use super::event_catcher::EventCatcher;
 
 pub struct EventDispatcher {}
@@ -12,19 +13,35 @@ impl EventDispatcher {
         EventDispatcher {}
     }
 
-    pub fn get(&self) -> anyhow::Result<Event> {
+    /// Waits for the next key event, or returns `Event::Tick` once `tick_interval` passes
+    /// without any key being pressed.
+    pub fn get(&self, tick_interval: Option<Duration>) -> anyhow::Result<Event> {
         let _guard = EventCatcher::new()?;
+        let started_at = Instant::now();
 
         loop {
-            let ct_event::Event::Key(ct_event::KeyEvent {
-                code,
-                modifiers,
-                kind,
-                ..
-            }): ct_event::Event = ct_event::read()?
-            else {
-                trace!("Received non-key event, ignoring");
-                continue;
+            if let Some(interval) = tick_interval {
+                let remaining = interval.saturating_sub(started_at.elapsed());
+                if !ct_event::poll(remaining)? {
+                    return Ok(Event::Tick);
+                }
+            }
+
+            let (code, modifiers, kind) = match ct_event::read()? {
+                ct_event::Event::Key(ct_event::KeyEvent {
+                    code,
+                    modifiers,
+                    kind,
+                    ..
+                }) => (code, modifiers, kind),
+                ct_event::Event::Resize(width, height) => {
+                    trace!("Terminal resized to {}x{}", width, height);
+                    return Ok(Event::Resize);
+                }
+                _ => {
+                    trace!("Received non-key event, ignoring");
+                    continue;
+                }
             };
 
             trace!(
@@ -41,7 +58,28 @@ impl EventDispatcher {
                 ct_event::KeyCode::Enter => return Ok(Event::Enter),
                 ct_event::KeyCode::Esc => return Ok(Event::Quit),
                 ct_event::KeyCode::Tab => return Ok(Event::Back),
-                ct_event::KeyCode::Backspace => return Ok(Event::RemoveCharacter),
+                ct_event::KeyCode::Backspace if modifiers == ct_event::KeyModifiers::ALT => {
+                    return Ok(Event::Edit(Edit::RemoveWord));
+                }
+                ct_event::KeyCode::Backspace => return Ok(Event::Edit(Edit::RemoveCharacter)),
+                ct_event::KeyCode::Delete => return Ok(Event::Edit(Edit::RemoveNextCharacter)),
+                ct_event::KeyCode::Left => return Ok(Event::Edit(Edit::MoveLeft)),
+                ct_event::KeyCode::Right => return Ok(Event::Edit(Edit::MoveRight)),
+                ct_event::KeyCode::Home => return Ok(Event::Edit(Edit::MoveToStart)),
+                ct_event::KeyCode::End => return Ok(Event::Edit(Edit::MoveToEnd)),
+                ct_event::KeyCode::F(1) => return Ok(Event::Hint),
+                ct_event::KeyCode::Char(c) if modifiers == ct_event::KeyModifiers::CONTROL => {
+                    match c {
+                        't' => return Ok(Event::Hint),
+                        'a' => return Ok(Event::Edit(Edit::MoveToStart)),
+                        'e' => return Ok(Event::Edit(Edit::MoveToEnd)),
+                        'w' => return Ok(Event::Edit(Edit::RemoveWord)),
+                        _ => {
+                            trace!("Unhandled control key, ignoring");
+                            continue;
+                        }
+                    }
+                }
                 ct_event::KeyCode::Char(c) => return Ok(Event::Character(c)),
                 _ => {
                     trace!("Unhandled key event, ignoring");
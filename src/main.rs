@@ -1,3 +1,4 @@
+mod cli;
 mod engine;
 mod events;
 mod renderer;
@@ -13,8 +14,16 @@ use crate::utils::{args, config, logging};
 
 fn main() -> anyhow::Result<()> {
     let args = args::parse()?;
-    let config = config::load(&args.config_path)?;
+    let mut config = config::load(&args.config_path)?;
     logging::init(&config.log_level, &config.log_dir_uri)?;
+    if let Some(tag_filter) = args.tag_filter {
+        config.session_tag_filter = Some(tag_filter);
+        config.parse_tag_filter()?;
+    }
+
+    if let Some(command) = args.command {
+        return cli::run(command, &config);
+    }
 
     let mut app = App::new(Rc::new(RefCell::new(config)))?;
     match app.run() {
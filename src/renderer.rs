@@ -1,31 +1,82 @@
 use anyhow::Context;
 use crossterm::cursor;
 use crossterm::execute;
-use log::trace;
-use std::cell::RefCell;
-use std::io::Write;
+use crossterm::style::Stylize;
+use log::{trace, warn};
+use std::cell::{Cell, RefCell};
 use std::io::stdout;
+use std::path::Path;
 use std::rc::Rc;
+use std::time::Duration;
 
-use crate::config::Config;
+use crate::config::{Config, ConfigField, FIELDS};
+use crate::engine::diff::{CharDiff, WordDiff};
+use crate::engine::game::RoundSummary;
+use crate::engine::language::Language;
+use crate::engine::line_editor::LineEditor;
+use crate::engine::matcher::MatchGrade;
+use crate::engine::statistics::{MasteryLevel, Statistics};
+use crate::types::{Direction, GameMode};
+use crate::utils::database::Phrase;
+
+mod layout;
+mod theme;
+
+use layout::Layout;
+use theme::{Role, Theme, no_color_requested};
+
+pub use theme::{THEMES, ThemeConfig};
 
 pub struct Renderer {
     config: Rc<RefCell<Config>>,
+    theme: Theme,
+    /// Layout of the screen being drawn, measured when it's cleared.
+    layout: Cell<Layout>,
 }
 
 impl Renderer {
     pub fn new(config: Rc<RefCell<Config>>) -> Self {
-        Renderer { config }
+        let config_dir = config.borrow().path.parent().map(Path::to_path_buf);
+        let no_color = no_color_requested();
+        let theme = Theme::load(
+            &config.borrow().theme,
+            &config_dir.unwrap_or_default(),
+            no_color,
+        )
+        .unwrap_or_else(|e| {
+            warn!("Failed to load theme, using the default one: {:?}", e);
+            let theme = Theme::default();
+            if no_color {
+                theme.without_colors()
+            } else {
+                theme
+            }
+        });
+        Renderer {
+            config,
+            theme,
+            layout: Cell::new(Layout::current()),
+        }
     }
 
-    pub fn render_main_menu(&self) -> anyhow::Result<()> {
+    /// Renders the main menu, noting if the phrases changed on disk.
+    pub fn render_main_menu(
+        &self,
+        game_mode: GameMode,
+        phrases_changed: bool,
+    ) -> anyhow::Result<()> {
         // TODO consider using crossterm to clear terminal and manipulate its content (for compatibility reasons)
-        // TODO let's find size of the terminal and render UI nicely at the top centered
-        // TODO Let's add some colors to the menu (something CyberPunk-themed)
         self.hide_cursor()?;
         self.clear_screen();
         self.render_logo();
-        self.render_main_menu_options();
+        self.render_main_menu_options(game_mode);
+        if phrases_changed {
+            let notice = "Phrases were updated on disk, the next game practises the new ones.";
+            self.print_line(&format!(
+                "   {}\n",
+                self.theme.paint(Role::Highlight, notice)
+            ));
+        }
 
         trace!("Main menu rendered");
         Ok(())
@@ -33,18 +84,25 @@ impl Renderer {
 
     pub fn render_settings_menu(
         &self,
-        user_input: Option<&str>,
-        placeholder_text: Option<&str>,
         config: &Config,
+        selected_field: Option<&ConfigField>,
+        user_input: &LineEditor,
+        editing: bool,
+        status: Option<&str>,
     ) -> anyhow::Result<()> {
         self.clear_screen();
         self.render_logo();
-        self.render_settings_options(config);
+        self.render_settings_options(config, selected_field);
+        if let Some(field) = selected_field {
+            self.print_line(&format!("   {}", field.help));
+        }
+        if let Some(status) = status {
+            self.print_line(&format!("   {}", status));
+        }
+        println!();
 
-        if placeholder_text.is_some() {
-            self.render_input_box(user_input, placeholder_text.unwrap())?;
-        } else if user_input.is_some() {
-            self.render_input_box(user_input, "Sorry, something went wrong...")?;
+        if editing {
+            self.render_input_box(user_input, "Enter new value...")?;
         } else {
             self.hide_cursor()?;
         }
@@ -53,62 +111,294 @@ impl Renderer {
         Ok(())
     }
 
-    // TODO overall game screens should have progress indicator, some nice looking art and colors (something CyberPunk-themed)
+    /// Renders the deck list; `matching` is the number of phrases passing the tag filter
+    /// across all decks.
+    pub fn render_deck_picker(
+        &self,
+        decks: &[(String, usize)],
+        matching: usize,
+        last_deck: Option<&str>,
+        user_input: &LineEditor,
+        editing_filter: bool,
+        status: Option<&str>,
+    ) -> anyhow::Result<()> {
+        self.clear_screen();
+        self.render_logo();
+        self.print_line("   Which deck do you want to practise?\n");
+        for (idx, (deck, count)) in decks.iter().enumerate() {
+            let marker = if Some(deck.as_str()) == last_deck {
+                ">"
+            } else {
+                " "
+            };
+            self.print_line(&format!(
+                "  {} {}  {} ({} phrases)",
+                marker,
+                self.key(&(idx + 1).to_string()),
+                deck,
+                count
+            ));
+        }
+        let marker = if last_deck.is_none() { ">" } else { " " };
+        self.print_line(&format!(
+            "  {} {}  All decks ({} matching phrases)",
+            marker,
+            self.key("A"),
+            matching
+        ));
+        println!();
+        self.render_option(
+            "Enter",
+            &format!("Continue with {}", last_deck.unwrap_or("all decks")),
+        );
+        self.render_option(
+            "T",
+            &format!(
+                "Tags: {}",
+                self.config.borrow().active_tag_filter().unwrap_or("any")
+            ),
+        );
+        self.render_option("B", "Back to main menu");
+        println!();
+        if let Some(status) = status {
+            self.print_line(&format!("   {}\n", status));
+        }
+
+        if editing_filter {
+            self.print_line(
+                "   Tags separated with spaces must all match, '|' separates alternatives",
+            );
+            self.print_line("   and '-' excludes a tag, e.g. 'news idiom | business -formal'.\n");
+            self.render_input_box(user_input, "Enter tag filter...")?;
+        } else if decks.len() > 9 {
+            self.render_input_box(user_input, "Enter deck number...")?;
+        } else {
+            self.hide_cursor()?;
+        }
+
+        trace!("Deck picker rendered");
+        Ok(())
+    }
+
+    // TODO overall game screens should have progress indicator and some nice looking art
     pub fn render_guessing_screen(
         &self,
-        original: &str,
-        user_input: Option<&str>,
+        phrase: &Phrase,
+        direction: Direction,
+        user_input: &LineEditor,
+        hint: Option<&str>,
+        countdown: Option<(Duration, Duration)>,
     ) -> anyhow::Result<()> {
         self.clear_screen();
         self.render_logo();
-        // TODO render proper guessing screen with some colors and maybe ASCII art (something CyberPunk-themed)
-        self.render_original_phrase(original);
+        if let Some((time_left, time_limit)) = countdown {
+            self.render_countdown(time_left, time_limit);
+        }
+        self.render_prompt(phrase, direction);
+        self.render_keyboard_hint(phrase, direction);
+        match hint {
+            Some(hint) => self.print_line(&format!(
+                "   Hint: {}\n",
+                self.theme.paint(Role::Highlight, hint)
+            )),
+            None => self.print_line("   Stuck? Press F1 or Ctrl-T for a hint.\n"),
+        }
         self.render_input_box(user_input, "Enter your answer...")?;
 
-        trace!("Game screen rendered for phrase: {}", original);
+        trace!(
+            "Game screen rendered for phrase: {} ({:?})",
+            phrase.original, direction
+        );
         Ok(())
     }
 
+    pub fn render_choice_screen(
+        &self,
+        phrase: &Phrase,
+        direction: Direction,
+        choices: &[String],
+    ) -> anyhow::Result<()> {
+        self.hide_cursor()?;
+        self.clear_screen();
+        self.render_logo();
+        self.render_prompt(phrase, direction);
+        for (idx, choice) in choices.iter().enumerate() {
+            self.print_line(&format!(
+                "    {}  {}",
+                self.key(&(idx + 1).to_string()),
+                choice
+            ));
+        }
+        println!();
+
+        trace!(
+            "Choice screen rendered for phrase: {} ({:?})",
+            phrase.original, direction
+        );
+        Ok(())
+    }
+
+    /// Renders the grade and accepted answers, along with the difference between the answer
+    /// and the closest accepted one unless it was right.
     pub fn render_feedback_screen(
         &self,
-        is_correct: bool,
-        correct_answer: &str,
+        grade: MatchGrade,
+        phrase: &Phrase,
+        direction: Direction,
+        diff: Option<&[WordDiff]>,
     ) -> anyhow::Result<()> {
+        let translations = phrase.answers(direction);
         self.clear_screen();
         self.render_logo();
 
-        // TODO introduce proper feedback screen with some colors and maybe ASCII art (something CyberPunk-themed)
-        if is_correct {
-            println!("Correct!");
-        } else {
-            println!("Incorrect! The correct answer was:\n");
-            println!("    {}", correct_answer);
+        let (role, heading) = match grade {
+            MatchGrade::Exact if translations.len() > 1 => {
+                (Role::Correct, "Correct! All accepted answers:")
+            }
+            MatchGrade::Exact => (Role::Correct, "Correct!"),
+            MatchGrade::Close => (
+                Role::Highlight,
+                "Almost — watch the typo! Accepted answers:",
+            ),
+            MatchGrade::Wrong => (Role::Incorrect, "Incorrect! The correct answer was:"),
+            MatchGrade::Timeout => (Role::Incorrect, "Time's up! The correct answer was:"),
+        };
+        self.print_line(&self.theme.paint(role, heading));
+        if grade != MatchGrade::Exact || translations.len() > 1 {
+            println!();
+            for translation in translations {
+                self.print_line(&format!("    {}", translation));
+            }
         }
         println!();
+        if let Some(diff) = diff {
+            self.render_answer_diff(diff);
+        }
+        self.render_phrase_details(phrase);
 
-        trace!("Feedback screen rendered, is_correct={}", is_correct);
+        trace!("Feedback screen rendered, grade={:?}", grade);
         Ok(())
     }
 
-    pub fn render_round_end_screen(&self) -> anyhow::Result<()> {
+    /// Renders the round results, with the score and the highest possible one in timed mode.
+    pub fn render_round_end_screen(
+        &self,
+        summary: &RoundSummary,
+        score: Option<(usize, usize)>,
+    ) -> anyhow::Result<()> {
+        self.hide_cursor()?;
         self.clear_screen();
         self.render_logo();
-        // TODO introduce proper round end screen with some colors and maybe ASCII art (something CyberPunk-themed)
-        println!("Round completed! Ready for the next one?\n");
-        println!("    [Enter]  Next game");
-        println!("    [B]      Back to main menu");
+        self.print_line("Round completed! Ready for the next one?\n");
+        self.render_round_summary(summary);
+        if let Some((score, max_score)) = score {
+            self.print_line(&format!(
+                "   Score:              {} of {}\n",
+                score, max_score
+            ));
+        }
+
+        self.render_option("Enter", "Next game");
+        let redrill_count = summary.phrases_to_redrill().len();
+        if redrill_count > 0 {
+            self.render_option(
+                "R",
+                &format!(
+                    "Re-drill phrases that needed more attempts ({})",
+                    redrill_count
+                ),
+            );
+        }
+        self.render_option("B", "Back to main menu");
         println!();
 
         trace!("Round end screen rendered");
         Ok(())
     }
 
+    pub fn render_statistics_screen(&self, statistics: &Statistics) -> anyhow::Result<()> {
+        const BAR_WIDTH: usize = 16;
+        const CHART_DAYS: usize = 7;
+
+        self.hide_cursor()?;
+        self.clear_screen();
+        self.render_logo();
+        self.print_line("   Statistics\n");
+        self.print_line(&format!(
+            "   Current streak: {} days   Longest streak: {} days   Reviews: {}\n",
+            statistics.current_streak, statistics.longest_streak, statistics.total_reviews
+        ));
+
+        let recent = &statistics.daily[statistics.daily.len().saturating_sub(CHART_DAYS)..];
+        let max_reviews = recent.iter().map(|day| day.reviews).max().unwrap_or(0);
+        let max_due = statistics
+            .upcoming
+            .iter()
+            .map(|(_, count)| *count)
+            .max()
+            .unwrap_or(0);
+        self.print_line(&format!(
+            "   {:<38}Due, next 7 days",
+            "Reviews, last 7 days"
+        ));
+        for (day, (due_date, due)) in recent.iter().zip(&statistics.upcoming) {
+            let reviewed = format!(
+                "{}  {:<BAR_WIDTH$} {:>3}",
+                day.date.format("%a %m-%d"),
+                bar(day.reviews, max_reviews, BAR_WIDTH),
+                day.reviews
+            );
+            self.print_line(&format!(
+                "   {:<38}{}  {:<BAR_WIDTH$} {:>3}",
+                reviewed,
+                due_date.format("%a %m-%d"),
+                bar(*due, max_due, BAR_WIDTH),
+                due
+            ));
+        }
+        println!();
+
+        let (reviews, first_try) = recent.iter().fold((0, 0), |(reviews, first_try), day| {
+            (reviews + day.reviews, first_try + day.first_try)
+        });
+        let trend: String = statistics
+            .daily
+            .iter()
+            .map(|day| sparkline_char(day.accuracy()))
+            .collect();
+        let mut accuracy = format!("   First-try accuracy, last 30 days: {}", trend);
+        if reviews > 0 {
+            accuracy.push_str(&format!(
+                "  ({:.0}% this week)",
+                first_try as f64 * 100.0 / reviews as f64
+            ));
+        }
+        self.print_line(&format!("{}\n", accuracy));
+
+        let max_phrases = statistics.mastery.values().copied().max().unwrap_or(0);
+        self.print_line("   Phrases by mastery");
+        for level in MasteryLevel::ALL {
+            let count = statistics.mastery.get(&level).copied().unwrap_or(0);
+            self.print_line(&format!(
+                "   {:<10}{:<BAR_WIDTH$} {:>4}",
+                mastery_label(level),
+                bar(count, max_phrases, BAR_WIDTH),
+                count
+            ));
+        }
+        println!();
+        self.render_option("B", "Back to main menu");
+        println!();
+
+        trace!("Statistics screen rendered");
+        Ok(())
+    }
+
     pub fn render_quit_screen(&self) -> anyhow::Result<()> {
         self.hide_cursor()?;
         self.clear_screen();
         self.render_logo();
-        // TODO introduce proper goodbye screen with some colors and maybe ASCII art (something CyberPunk-themed)
-        println!("Goodbye!");
+        self.print_line("Goodbye!");
         println!();
 
         trace!("Goodbye screen rendered");
@@ -128,87 +418,350 @@ impl Renderer {
     }
     fn clear_screen(&self) {
         print!("\x1B[2J\x1B[1;1H");
-        trace!("Screen cleared");
+        self.layout.set(Layout::current());
+        trace!("Screen cleared, layout: {:?}", self.layout.get());
+    }
+
+    /// Prints the text in the content column, wrapping lines too long for it.
+    fn print_line(&self, text: &str) {
+        let layout = self.layout.get();
+        let margin = " ".repeat(layout.margin());
+        for line in text.split('\n') {
+            for line in layout.wrap(line) {
+                if line.is_empty() {
+                    println!();
+                } else {
+                    println!("{}{}", margin, line);
+                }
+            }
+        }
     }
 
     fn render_logo(&self) {
         println!();
-        println!("   ██████╗ ██╗  ██╗██████╗  █████╗ ███████╗███████╗██╗   ██╗   ");
-        println!("   ██╔══██╗██║  ██║██╔══██╗██╔══██╗██╔════╝██╔════╝╚██╗ ██╔╝   ");
-        println!("   ██████╔╝███████║██████╔╝███████║███████╗█████╗   ╚████╔╝    ");
-        println!("   ██╔═══╝ ██╔══██║██╔══██╗██╔══██║╚════██║██╔══╝    ╚██╔╝     ");
-        println!("   ██║     ██║  ██║██║  ██║██║  ██║███████║███████╗   ██║      ");
-        println!("   ╚═╝     ╚═╝  ╚═╝╚═╝  ╚═╝╚═╝  ╚═╝╚══════╝╚══════╝   ╚═╝      ");
+        for line in self.layout.get().logo() {
+            self.print_line(&self.theme.paint(Role::Logo, line));
+        }
         println!();
 
         trace!("Logo rendered");
     }
 
-    fn render_main_menu_options(&self) {
-        println!("   What do you want to do?\n");
-        println!("    [Enter]  New game");
-        println!("    [S]      Settings");
-        println!("    [Q]      Quit");
+    fn render_main_menu_options(&self, game_mode: GameMode) {
+        self.print_line("   What do you want to do?\n");
+        self.render_option("Enter", "New game");
+        self.render_option("M", &format!("Mode: {}", game_mode_label(game_mode)));
+        self.render_option("S", "Settings");
+        self.render_option("T", "Statistics");
+        self.render_option("Q", "Quit");
         println!();
 
         trace!("Main menu options rendered");
     }
 
-    fn render_input_box(&self, text: Option<&str>, placeholder_text: &str) -> anyhow::Result<()> {
-        let box_width = self.config.borrow().input_box_width;
+    /// Draws the input box, as wide as configured unless the terminal is narrower, with the
+    /// typed text wrapped over as many lines as it needs and the cursor placed in it.
+    fn render_input_box(&self, input: &LineEditor, placeholder_text: &str) -> anyhow::Result<()> {
+        let layout = self.layout.get();
+        let box_width = self
+            .config
+            .borrow()
+            .input_box_width
+            .min(layout.content_width().saturating_sub(4))
+            .max(4);
         let text_width = box_width - 2;
+        let margin = " ".repeat(layout.margin());
 
-        let top_border = format!("┌{}┐", "─".repeat(box_width));
-        let bottom_border = format!("└{}┘", "─".repeat(box_width));
-        let mut cursor_position = 0;
-        let text_lines = if let Some(text) = text {
-            // TODO split text into chunks per line
-            // TODO move cursor to the end of last character (for text)
-            // TODO combine formats in a loop for multiple lines
-            let mut lines = Vec::new();
-            for chunk in text.chars().collect::<Vec<char>>().chunks(text_width) {
-                let line = chunk.iter().collect::<String>();
-                lines.push(format!(
-                    "│ {}{} │",
-                    line,
-                    " ".repeat(text_width - line.len())
-                ));
-                cursor_position = line.len() + 3;
-            }
-            lines.join("\n")
+        let border = |text: &str| self.theme.paint(Role::Border, text);
+        let top_border = border(&format!("┌{}┐", "─".repeat(box_width)));
+        let bottom_border = border(&format!("└{}┘", "─".repeat(box_width)));
+        let text_lines = if input.is_empty() {
+            let placeholder_text = truncate(placeholder_text, text_width);
+            let padding = " ".repeat(text_width - placeholder_text.chars().count());
+            vec![self.theme.paint(Role::Placeholder, &placeholder_text) + &padding]
         } else {
-            cursor_position = 3;
-            format!(
-                "│ \x1b[90m{}\x1b[0m{} │",
-                placeholder_text,
-                " ".repeat(text_width - placeholder_text.len())
-            )
+            let chars: Vec<char> = input.text().chars().collect();
+            let mut lines: Vec<String> = chars
+                .chunks(text_width)
+                .map(|chunk| {
+                    let padding = " ".repeat(text_width - chunk.len());
+                    chunk.iter().collect::<String>() + &padding
+                })
+                .collect();
+            // Once the last line is full the cursor after it goes to a new one
+            if chars.len().is_multiple_of(text_width) {
+                lines.push(" ".repeat(text_width));
+            }
+            lines
         };
 
         self.show_cursor()?;
-        println!(" {} ", top_border);
-        println!(" {} ", text_lines);
-        println!(" {} ", bottom_border);
-        print!("\x1b[2A\x1b[{}C", cursor_position);
-        std::io::stdout().flush()?;
+        println!("{} {} ", margin, top_border);
+        for line in &text_lines {
+            println!("{} {} {} {} ", margin, border("│"), line, border("│"));
+        }
+        println!("{} {} ", margin, bottom_border);
+        let (row, column) = (input.cursor() / text_width, input.cursor() % text_width);
+        execute!(
+            stdout(),
+            cursor::MoveUp((text_lines.len() - row + 1) as u16),
+            cursor::MoveToColumn((margin.len() + 3 + column) as u16)
+        )
+        .context("Failed to move cursor to the input box")?;
 
         trace!("Input box rendered");
         Ok(())
     }
 
-    fn render_settings_options(&self, config: &Config) {
-        println!("   Settings\n");
-        println!("    [P] Phrases per round: {}", config.phrases_per_round);
-        println!("    [S] Save");
-        println!("    [B] Back to main menu");
+    fn render_settings_options(&self, config: &Config, selected_field: Option<&ConfigField>) {
+        self.print_line("   Settings\n");
+        for field in FIELDS {
+            let marker = match selected_field {
+                Some(selected) if selected.key == field.key => ">",
+                _ => " ",
+            };
+            let value = field.value(config);
+            let value = if value.is_empty() {
+                "(not set)"
+            } else {
+                &value
+            };
+            self.print_line(&format!(
+                "  {} {} {:<18} {}",
+                marker,
+                self.key(&field.key.to_ascii_uppercase().to_string()),
+                field.label,
+                value
+            ));
+        }
+        self.print_line(&format!("    {} Save", self.key("S")));
+        self.print_line(&format!("    {} Back to main menu", self.key("B")));
         println!();
 
         trace!("Settings options rendered");
     }
 
-    fn render_original_phrase(&self, original: &str) {
-        println!("   Sentence: {}\n", original);
-        trace!("Original phrase rendered: {}", original);
+    fn render_round_summary(&self, summary: &RoundSummary) {
+        if summary.phrases.is_empty() {
+            return;
+        }
+
+        // The phrase column takes what's left of the content column, up to 40 characters
+        let phrase_width = self
+            .layout
+            .get()
+            .content_width()
+            .saturating_sub(21)
+            .clamp(10, 40);
+        self.print_line(&format!(
+            "   {:<phrase_width$} {:>8} {:>8}",
+            "Phrase", "Attempts", "Time"
+        ));
+        for item in &summary.phrases {
+            let prompt = truncate(item.phrase.prompt(item.direction), phrase_width);
+            let attempts = if item.recognized {
+                item.attempts.to_string()
+            } else {
+                format!("{} (x)", item.attempts)
+            };
+            self.print_line(&format!(
+                "   {:<phrase_width$} {:>8} {:>7.1}s",
+                prompt,
+                attempts,
+                item.time_spent.as_secs_f64()
+            ));
+        }
+        println!();
+
+        let first_try = summary.first_try_count();
+        self.print_line(&format!(
+            "   First-try accuracy: {:.0}% ({}/{})",
+            first_try as f64 * 100.0 / summary.phrases.len() as f64,
+            first_try,
+            summary.phrases.len()
+        ));
+        self.print_line(&format!(
+            "   Total time:         {:.1}s",
+            summary.total_time().as_secs_f64()
+        ));
+        let hardest = summary.hardest(3);
+        if !hardest.is_empty() {
+            self.print_line("   Hardest phrases:");
+            for item in hardest {
+                self.print_line(&format!(
+                    "     - {} ({} attempts)",
+                    item.phrase.prompt(item.direction),
+                    item.attempts
+                ));
+            }
+        }
+        println!();
+
+        trace!("Round summary rendered");
+    }
+
+    fn render_prompt(&self, phrase: &Phrase, direction: Direction) {
+        if let (Some(from), Some(to)) = (
+            phrase.prompt_language(direction),
+            phrase.answer_language(direction),
+        ) {
+            self.print_line(&format!("   Translate from {} to {}\n", from, to));
+        }
+        let prompt = phrase.prompt(direction);
+        match direction {
+            Direction::Forward => self.print_line(&format!("   Sentence: {}\n", prompt)),
+            Direction::Reverse => self.print_line(&format!("   Translation: {}\n", prompt)),
+        }
+        trace!("Prompt rendered: {}", prompt);
+    }
+
+    fn render_countdown(&self, time_left: Duration, time_limit: Duration) {
+        const BAR_WIDTH: usize = 30;
+        let seconds_left = time_left.as_secs_f64().ceil() as usize;
+        self.print_line(&format!(
+            "   Time left: {:>3}s  {}\n",
+            seconds_left,
+            self.theme.paint(
+                Role::Highlight,
+                &bar(seconds_left, time_limit.as_secs() as usize, BAR_WIDTH)
+            )
+        ));
+        trace!("Countdown rendered: {:?} of {:?}", time_left, time_limit);
+    }
+
+    /// Lists letters of the answer's language that are missing from a US keyboard layout.
+    fn render_keyboard_hint(&self, phrase: &Phrase, direction: Direction) {
+        let Some(language) = phrase.answer_language(direction).and_then(Language::find) else {
+            return;
+        };
+        if language.special_characters.is_empty() {
+            return;
+        }
+        let characters: Vec<String> = language
+            .special_characters
+            .iter()
+            .map(char::to_string)
+            .collect();
+        self.print_line(&format!(
+            "   {} letters: {}\n",
+            language.name,
+            characters.join(" ")
+        ));
+
+        trace!("Keyboard hint rendered for {}", language.name);
+    }
+
+    /// Prints the answer with expected words it lacks underlined in green, wrong letters and
+    /// extra words struck out in red and words given in the wrong place in yellow.
+    fn render_answer_diff(&self, diff: &[WordDiff]) {
+        let words: Vec<String> = diff
+            .iter()
+            .map(|word| match word {
+                WordDiff::Same(word) => word.clone(),
+                WordDiff::Typo(chars) => chars
+                    .iter()
+                    .map(|c| match *c {
+                        CharDiff::Same(c) => c.to_string(),
+                        CharDiff::Extra(c) => self.paint_extra(&c.to_string()),
+                        CharDiff::Missing(c) => self.paint_missing(&c.to_string()),
+                    })
+                    .collect(),
+                WordDiff::Extra(word) => self.paint_extra(word),
+                WordDiff::Missing(word) => self.paint_missing(word),
+                WordDiff::Moved(word) => self.theme.paint(Role::Highlight, word),
+            })
+            .collect();
+        self.print_line(&format!("   Your answer:  {}", words.join(" ")));
+        self.print_line(&format!(
+            "   {}  {}  {}\n",
+            self.paint_missing("missing"),
+            self.paint_extra("wrong"),
+            self.theme.paint(Role::Highlight, "misplaced")
+        ));
+        trace!("Answer diff rendered: {:?}", diff);
+    }
+
+    fn paint_missing(&self, text: &str) -> String {
+        self.theme
+            .paint(Role::Correct, &text.underlined().to_string())
+    }
+
+    fn paint_extra(&self, text: &str) -> String {
+        self.theme
+            .paint(Role::Incorrect, &text.crossed_out().to_string())
+    }
+
+    /// Prints a menu option with the key padded to line up the labels.
+    fn render_option(&self, key: &str, label: &str) {
+        let padding = " ".repeat(7usize.saturating_sub(key.chars().count()));
+        self.print_line(&format!("    {}{} {}", self.key(key), padding, label));
+    }
+
+    /// Returns the key of a menu option in brackets, e.g. `[Enter]`.
+    fn key(&self, key: &str) -> String {
+        self.theme.paint(Role::MenuKey, &format!("[{}]", key))
+    }
+
+    fn render_phrase_details(&self, phrase: &Phrase) {
+        if let Some(example) = &phrase.example {
+            self.print_line(&format!("   Example: {}", example));
+        }
+        if let Some(notes) = &phrase.notes {
+            self.print_line(&format!("   Notes:   {}", notes));
+        }
+        if !phrase.tags.is_empty() {
+            self.print_line(&format!("   Tags:    {}", phrase.tags.join(", ")));
+        }
+        if phrase.example.is_some() || phrase.notes.is_some() || !phrase.tags.is_empty() {
+            println!();
+        }
+
+        trace!("Phrase details rendered: {:?}", phrase);
+    }
+}
+
+/// Shortens `text` to at most `width` characters, marking the cut with an ellipsis.
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+/// Draws a horizontal bar of `value` relative to `max`, at most `width` characters long.
+fn bar(value: usize, max: usize, width: usize) -> String {
+    if max == 0 {
+        return String::new();
+    }
+    let length = (value * width).div_ceil(max);
+    "█".repeat(length)
+}
+
+/// Returns a block character as tall as `ratio` (0.0-1.0), or a dot when there's no value.
+fn sparkline_char(ratio: Option<f64>) -> char {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    match ratio {
+        Some(ratio) => BLOCKS[((ratio * 7.0).round() as usize).min(7)],
+        None => '·',
+    }
+}
+
+fn mastery_label(level: MasteryLevel) -> &'static str {
+    match level {
+        MasteryLevel::New => "New",
+        MasteryLevel::Learning => "Learning",
+        MasteryLevel::Young => "Young",
+        MasteryLevel::Mature => "Mature",
+    }
+}
+
+fn game_mode_label(game_mode: GameMode) -> &'static str {
+    match game_mode {
+        GameMode::Typed => "typed answers",
+        GameMode::MultipleChoice => "multiple choice",
+        GameMode::Timed => "timed challenge",
     }
 }
 
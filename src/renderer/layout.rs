@@ -0,0 +1,166 @@
+use crossterm::terminal;
+use log::trace;
+
+/// Size assumed when the terminal can't be queried, e.g. when the output is redirected.
+const DEFAULT_SIZE: (u16, u16) = (80, 24);
+/// Widest the content column gets, it's centered in wider terminals.
+const MAX_CONTENT_WIDTH: usize = 80;
+
+const FULL_LOGO: [&str; 6] = [
+    "   ██████╗ ██╗  ██╗██████╗  █████╗ ███████╗███████╗██╗   ██╗",
+    "   ██╔══██╗██║  ██║██╔══██╗██╔══██╗██╔════╝██╔════╝╚██╗ ██╔╝",
+    "   ██████╔╝███████║██████╔╝███████║███████╗█████╗   ╚████╔╝ ",
+    "   ██╔═══╝ ██╔══██║██╔══██╗██╔══██║╚════██║██╔══╝    ╚██╔╝  ",
+    "   ██║     ██║  ██║██║  ██║██║  ██║███████║███████╗   ██║   ",
+    "   ╚═╝     ╚═╝  ╚═╝╚═╝  ╚═╝╚═╝  ╚═╝╚══════╝╚══════╝   ╚═╝   ",
+];
+const SMALL_LOGO: [&str; 3] = [
+    "   ┏━┓╻ ╻┏━┓┏━┓┏━┓┏━╸╻ ╻",
+    "   ┣━┛┣━┫┣┳┛┣━┫┗━┓┣╸ ┗┳┛",
+    "   ╹  ╹ ╹╹┗╸╹ ╹┗━┛┗━╸ ╹ ",
+];
+const TEXT_LOGO: [&str; 1] = ["   P H R A S E Y"];
+/// Terminal height below which the full logo would push the rest of a screen out of view.
+const FULL_LOGO_MIN_HEIGHT: usize = 32;
+const SMALL_LOGO_MIN_HEIGHT: usize = 18;
+
+/// Placement of the content in the terminal, measured whenever a screen is drawn.
+///
+/// Screens are drawn in a column at most `MAX_CONTENT_WIDTH` wide, centered horizontally
+/// and starting at the top. Lines too long for the column are wrapped at spaces.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Layout {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Layout {
+    /// Measures the terminal, falling back to 80x24 if that fails or the terminal doesn't
+    /// report its size.
+    pub fn current() -> Self {
+        let (width, height) = match terminal::size() {
+            Ok((width, height)) if width > 0 && height > 0 => (width, height),
+            result => {
+                trace!("Unknown terminal size, assuming default: {:?}", result);
+                DEFAULT_SIZE
+            }
+        };
+        Layout {
+            width: width as usize,
+            height: height as usize,
+        }
+    }
+
+    pub fn content_width(&self) -> usize {
+        self.width.min(MAX_CONTENT_WIDTH)
+    }
+
+    /// Returns the number of columns left of the content.
+    pub fn margin(&self) -> usize {
+        (self.width - self.content_width()) / 2
+    }
+
+    /// Returns the largest logo fitting the terminal.
+    pub fn logo(&self) -> &'static [&'static str] {
+        let fits = |logo: &[&str], min_height: usize| {
+            self.height >= min_height && logo.iter().all(|line| width(line) <= self.content_width())
+        };
+        if fits(&FULL_LOGO, FULL_LOGO_MIN_HEIGHT) {
+            &FULL_LOGO
+        } else if fits(&SMALL_LOGO, SMALL_LOGO_MIN_HEIGHT) {
+            &SMALL_LOGO
+        } else {
+            &TEXT_LOGO
+        }
+    }
+
+    /// Splits the line into lines fitting the content column, breaking at spaces. Wrapped
+    /// parts are indented like the line itself, words longer than the column are cut.
+    pub fn wrap(&self, line: &str) -> Vec<String> {
+        let max_width = self.content_width();
+        if width(line) <= max_width {
+            return vec![line.to_string()];
+        }
+
+        let text = line.trim_start_matches(' ');
+        let indent = " ".repeat((line.len() - text.len()).min(max_width / 2));
+        let mut lines = Vec::new();
+        let mut current = indent.clone();
+        for word in text.split(' ') {
+            let is_empty = current.len() == indent.len();
+            if !is_empty && width(&current) + 1 + width(word) > max_width {
+                lines.push(std::mem::replace(&mut current, indent.clone()));
+            }
+            if current.len() > indent.len() {
+                current.push(' ');
+            }
+            current.push_str(word);
+            while width(&current) > max_width {
+                let (head, tail) = split_at_width(&current, max_width);
+                lines.push(head);
+                current = format!("{}{}", indent, tail);
+            }
+        }
+        lines.push(current);
+        lines
+    }
+}
+
+/// Returns the number of columns the text takes, not counting ANSI escape sequences.
+pub fn width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in text.chars() {
+        match c {
+            '\x1b' => in_escape = true,
+            c if in_escape => in_escape = !c.is_ascii_alphabetic(),
+            _ => width += 1,
+        }
+    }
+    width
+}
+
+/// Splits the text after `max_width` columns, keeping ANSI escape sequences whole.
+fn split_at_width(text: &str, max_width: usize) -> (String, String) {
+    let mut width = 0;
+    let mut in_escape = false;
+    for (idx, c) in text.char_indices() {
+        match c {
+            '\x1b' => in_escape = true,
+            c if in_escape => in_escape = !c.is_ascii_alphabetic(),
+            _ if width == max_width => return (text[..idx].to_string(), text[idx..].to_string()),
+            _ => width += 1,
+        }
+    }
+    (text.to_string(), String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_centers_and_wraps_content() {
+        let wide = Layout {
+            width: 120,
+            height: 40,
+        };
+        let narrow = Layout {
+            width: 24,
+            height: 20,
+        };
+
+        assert_eq!(wide.margin(), 20);
+        assert_eq!(wide.logo().len(), FULL_LOGO.len());
+        assert_eq!(narrow.logo().len(), SMALL_LOGO.len());
+        assert_eq!(narrow.margin(), 0);
+        assert_eq!(
+            narrow.wrap("   Sentence: Issue an \x1b[31mappeal\x1b[0m"),
+            vec!["   Sentence: Issue an", "   \x1b[31mappeal\x1b[0m"]
+        );
+        assert_eq!(
+            narrow.wrap("   Supercalifragilisticexpialidocious"),
+            vec!["   Supercalifragilistice", "   xpialidocious"]
+        );
+    }
+}
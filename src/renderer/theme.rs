@@ -0,0 +1,337 @@
+use anyhow::Context;
+use crossterm::style::{Color, Stylize};
+use log::{trace, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Names of the built-in themes, the first one being the default.
+pub const THEMES: [&str; 3] = ["cyberpunk", "classic", "monochrome"];
+
+/// Part of the screen painted in a colour of its own.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    Logo,
+    /// Keys of menu options, e.g. `[Enter]`.
+    MenuKey,
+    Correct,
+    Incorrect,
+    /// Things worth noticing that are neither right nor wrong, e.g. hints or the countdown.
+    Highlight,
+    /// Text shown in an empty input box.
+    Placeholder,
+    Border,
+}
+
+impl Role {
+    const ALL: [Role; 7] = [
+        Role::Logo,
+        Role::MenuKey,
+        Role::Correct,
+        Role::Incorrect,
+        Role::Highlight,
+        Role::Placeholder,
+        Role::Border,
+    ];
+
+    /// Returns the name of the role in the config and theme files.
+    fn name(&self) -> &'static str {
+        match self {
+            Role::Logo => "logo",
+            Role::MenuKey => "menu_key",
+            Role::Correct => "correct",
+            Role::Incorrect => "incorrect",
+            Role::Highlight => "highlight",
+            Role::Placeholder => "placeholder",
+            Role::Border => "border",
+        }
+    }
+}
+
+/// Theme settings, the `[theme]` table of the config file.
+///
+/// Colours of the built-in theme are overridden by the ones of the theme file, which are
+/// overridden by the `[theme.colors]` table. Both hold colours by role name, e.g.
+/// `correct = "bold #39ff14"`.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Name of the built-in theme, one of `THEMES`.
+    pub name: String,
+    /// TOML file with colours of the theme, relative to the config file.
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub colors: BTreeMap<String, String>,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        ThemeConfig {
+            name: THEMES[0].to_string(),
+            file: None,
+            colors: BTreeMap::new(),
+        }
+    }
+}
+
+impl ThemeConfig {
+    /// Checks the theme name and the colours of the config file. The theme file isn't
+    /// checked, it's read only when the screens are drawn.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        Theme::built_in_with_colors(self).map(|_| ())
+    }
+}
+
+/// How text of a role is painted: in a colour, bold, or both.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Style {
+    color: Option<Color>,
+    bold: bool,
+}
+
+impl Style {
+    const fn new(color: Option<Color>, bold: bool) -> Self {
+        Style { color, bold }
+    }
+
+    /// Parses space-separated words: `bold`, `none` for no colour, a colour name such as
+    /// `dark_cyan`, an ANSI colour number (0-255) or a `#rrggbb` code.
+    fn parse(value: &str) -> anyhow::Result<Self> {
+        let mut style = Style::default();
+        for word in value.split_whitespace() {
+            let word = word.to_lowercase();
+            match word.as_str() {
+                "bold" => style.bold = true,
+                "none" => style.color = None,
+                _ => style.color = Some(parse_color(&word)?),
+            }
+        }
+        Ok(style)
+    }
+}
+
+fn parse_color(word: &str) -> anyhow::Result<Color> {
+    if let Some(hex) = word.strip_prefix('#') {
+        let channel = |idx: usize| {
+            hex.get(idx..idx + 2)
+                .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+        };
+        if let (6, Some(r), Some(g), Some(b)) = (hex.len(), channel(0), channel(2), channel(4)) {
+            return Ok(Color::Rgb { r, g, b });
+        }
+    } else if let Ok(value) = word.parse::<u8>() {
+        return Ok(Color::AnsiValue(value));
+    } else if let Ok(color) = Color::try_from(word) {
+        return Ok(color);
+    }
+    anyhow::bail!("Unknown colour '{}'.", word)
+}
+
+/// Whether the `NO_COLOR` environment variable asks for output without colours, see
+/// https://no-color.org.
+pub fn no_color_requested() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+/// Reads colours by role name from a theme file.
+fn read_colors(path: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read theme file {:?}", path))?;
+    toml_edit::de::from_str(&content)
+        .with_context(|| format!("Failed to parse theme file {:?}", path))
+}
+
+/// Colours the screens are painted with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    styles: [Style; Role::ALL.len()],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::built_in(THEMES[0]).expect("Default theme is built in")
+    }
+}
+
+impl Theme {
+    /// Builds the configured theme, looking for the theme file next to the config file.
+    /// A theme file that can't be used is skipped with a warning. Colours are left out if
+    /// `no_color` is set, see `no_color_requested`.
+    pub fn load(config: &ThemeConfig, config_dir: &Path, no_color: bool) -> anyhow::Result<Self> {
+        let mut theme = Theme::built_in_with_colors(&ThemeConfig {
+            colors: BTreeMap::new(),
+            ..config.clone()
+        })?;
+        if let Some(file) = &config.file {
+            let path = config_dir.join(file);
+            match read_colors(&path).and_then(|colors| {
+                let mut with_file = theme.clone();
+                with_file.set_colors(&colors)?;
+                Ok(with_file)
+            }) {
+                Ok(with_file) => theme = with_file,
+                Err(e) => warn!("Skipping theme file {:?}: {:?}", path, e),
+            }
+        }
+        theme.set_colors(&config.colors)?;
+
+        if no_color {
+            theme = theme.without_colors();
+        }
+        Ok(theme)
+    }
+
+    /// Returns the theme with only the bold styles left.
+    pub fn without_colors(mut self) -> Self {
+        trace!("Leaving colours out of the theme");
+        for style in &mut self.styles {
+            style.color = None;
+        }
+        self
+    }
+
+    /// Returns the built-in theme of the config with the colours of the `[theme.colors]`
+    /// table applied.
+    fn built_in_with_colors(config: &ThemeConfig) -> anyhow::Result<Self> {
+        let mut theme = Theme::built_in(&config.name).with_context(|| {
+            format!(
+                "Unknown theme '{}', expected one of: {}.",
+                config.name,
+                THEMES.join(", ")
+            )
+        })?;
+        theme.set_colors(&config.colors)?;
+        Ok(theme)
+    }
+
+    fn built_in(name: &str) -> Option<Self> {
+        let rgb = |r, g, b| Some(Color::Rgb { r, g, b });
+        let styles = match name {
+            "cyberpunk" => [
+                Style::new(rgb(255, 43, 214), true),
+                Style::new(rgb(0, 240, 255), true),
+                Style::new(rgb(57, 255, 20), false),
+                Style::new(rgb(255, 49, 88), false),
+                Style::new(rgb(252, 238, 10), false),
+                Style::new(rgb(118, 96, 168), false),
+                Style::new(rgb(0, 240, 255), false),
+            ],
+            "classic" => [
+                Style::new(None, false),
+                Style::new(Some(Color::Yellow), false),
+                Style::new(Some(Color::Green), false),
+                Style::new(Some(Color::Red), false),
+                Style::new(Some(Color::Yellow), false),
+                Style::new(Some(Color::DarkGrey), false),
+                Style::new(None, false),
+            ],
+            "monochrome" => [
+                Style::new(None, true),
+                Style::new(None, true),
+                Style::new(None, true),
+                Style::new(None, true),
+                Style::new(None, true),
+                Style::new(None, false),
+                Style::new(None, false),
+            ],
+            _ => return None,
+        };
+        Some(Theme { styles })
+    }
+
+    fn set_colors(&mut self, colors: &BTreeMap<String, String>) -> anyhow::Result<()> {
+        for (name, value) in colors {
+            let role = Role::ALL
+                .iter()
+                .position(|role| role.name() == name)
+                .with_context(|| format!("Unknown theme colour '{}'.", name))?;
+            self.styles[role] = Style::parse(value)
+                .with_context(|| format!("Invalid theme colour {} = '{}'", name, value))?;
+        }
+        Ok(())
+    }
+
+    /// Returns the text painted in the style of the role.
+    pub fn paint(&self, role: Role, text: &str) -> String {
+        let style = self.styles[role as usize];
+        let mut styled = text.stylize();
+        if let Some(color) = style.color {
+            styled = styled.with(color);
+        }
+        if style.bold {
+            styled = styled.bold();
+        }
+        styled.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_overrides_built_in_colours() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        std::fs::write(
+            dir.path().join("theme.toml"),
+            "logo = \"bold #ff8000\"\ncorrect = \"42\"\n",
+        )
+        .unwrap();
+        let config = ThemeConfig {
+            name: "monochrome".to_string(),
+            file: Some("theme.toml".to_string()),
+            colors: BTreeMap::from([("correct".to_string(), "dark_green".to_string())]),
+        };
+
+        let theme = Theme::load(&config, dir.path(), false).unwrap();
+
+        let logo = Style::new(
+            Some(Color::Rgb {
+                r: 255,
+                g: 128,
+                b: 0,
+            }),
+            true,
+        );
+        assert_eq!(theme.styles[Role::Logo as usize], logo);
+        assert_eq!(
+            theme.styles[Role::Correct as usize],
+            Style::new(Some(Color::DarkGreen), false)
+        );
+        assert_eq!(theme.paint(Role::Border, "│"), "│");
+        assert!(Style::parse("#ff80").is_err());
+
+        let missing_file = ThemeConfig {
+            file: Some("missing.toml".to_string()),
+            ..config
+        };
+        assert!(missing_file.validate().is_ok());
+        let theme = Theme::load(&missing_file, dir.path(), false).unwrap();
+        assert_eq!(theme.styles[Role::Logo as usize], Style::new(None, true));
+        assert_eq!(
+            theme.styles[Role::Correct as usize],
+            Style::new(Some(Color::DarkGreen), false)
+        );
+        assert!(
+            ThemeConfig {
+                name: "vaporwave".to_string(),
+                ..Default::default()
+            }
+            .validate()
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_load_without_colours() {
+        let config = ThemeConfig {
+            colors: BTreeMap::from([("logo".to_string(), "bold red".to_string())]),
+            ..Default::default()
+        };
+
+        let theme = Theme::load(&config, Path::new(""), true).unwrap();
+
+        assert_eq!(theme.styles[Role::Logo as usize], Style::new(None, true));
+        assert!(theme.styles.iter().all(|style| style.color.is_none()));
+    }
+}
@@ -10,3 +10,86 @@ pub enum LogLevel {
     Debug,
     Trace,
 }
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum SchedulerAlgorithm {
+    #[default]
+    Sm2,
+    Fsrs,
+}
+
+/// Direction in which phrases are quizzed during a game.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone)]
+#[serde(rename_all = "lowercase")]
+pub enum QuizDirection {
+    #[default]
+    Forward,
+    Reverse,
+    /// Forward or reverse, picked per phrase.
+    Random,
+}
+
+impl QuizDirection {
+    pub fn directions(&self) -> &'static [Direction] {
+        match self {
+            QuizDirection::Forward => &[Direction::Forward],
+            QuizDirection::Reverse => &[Direction::Reverse],
+            QuizDirection::Random => &[Direction::Forward, Direction::Reverse],
+        }
+    }
+}
+
+/// Direction of a single question: forward asks for the translation of the original
+/// phrase, reverse asks for the original phrase given its translation.
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Hash, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    #[default]
+    Forward,
+    Reverse,
+}
+
+impl Direction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Forward => "forward",
+            Direction::Reverse => "reverse",
+        }
+    }
+}
+
+impl std::str::FromStr for Direction {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> anyhow::Result<Self> {
+        match value {
+            "forward" => Ok(Direction::Forward),
+            "reverse" => Ok(Direction::Reverse),
+            _ => anyhow::bail!("Unknown direction: {}", value),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum GameMode {
+    /// The answer is typed in.
+    #[default]
+    Typed,
+    /// The answer is picked from a few candidates with number keys.
+    MultipleChoice,
+    /// The answer is typed in before a countdown runs out, and rounds are scored on speed.
+    Timed,
+}
+
+impl GameMode {
+    /// Returns the next mode, used to cycle through modes in menus.
+    pub fn next(&self) -> Self {
+        match self {
+            GameMode::Typed => GameMode::MultipleChoice,
+            GameMode::MultipleChoice => GameMode::Timed,
+            GameMode::Timed => GameMode::Typed,
+        }
+    }
+}
@@ -1,7 +1,9 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use log::debug;
 use std::path::PathBuf;
 
+use crate::utils::database::PhraseId;
+
 pub fn parse() -> anyhow::Result<Args> {
     Args::new()
 }
@@ -15,6 +17,55 @@ pub struct Args {
         help = "Path to the configuration file"
     )]
     pub config_path: PathBuf,
+
+    #[arg(
+        short,
+        long,
+        help = "Practise or list only phrases with matching tags, e.g. 'news idiom | business -formal'"
+    )]
+    pub tag_filter: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Non-interactive operations on the phrase database. Without a command the game starts.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    #[command(about = "Add a phrase; separate alternative translations with '|'")]
+    Add {
+        #[arg(help = "Phrase in the language being learned")]
+        original: String,
+        #[arg(help = "Accepted translation(s) of the phrase")]
+        translation: String,
+        #[arg(long, help = "Deck to add the phrase to")]
+        deck: Option<String>,
+        #[arg(long, help = "Tags of the phrase separated with ';'")]
+        tags: Option<String>,
+    },
+    #[command(about = "List phrases with their ids")]
+    List,
+    #[command(about = "Remove a phrase by its id")]
+    Remove {
+        #[arg(help = "Id of the phrase, as shown by 'list'")]
+        id: PhraseId,
+    },
+    #[command(
+        about = "Import phrases from a CSV file or an Anki export into the deck named after it, skipping the ones already stored"
+    )]
+    Import {
+        #[arg(
+            help = "Path to the CSV file, Anki package (.apkg) or Anki notes in plain text (.txt)"
+        )]
+        file: PathBuf,
+    },
+    #[command(about = "Export all phrases to a CSV file or Anki notes in plain text")]
+    Export {
+        #[arg(help = "Path to the CSV file, or a .txt file to import into Anki")]
+        file: PathBuf,
+    },
+    #[command(about = "Show learning statistics")]
+    Stats,
 }
 
 impl Args {
@@ -1,20 +1,107 @@
+mod field;
+
 use anyhow::Context;
 use log::{debug, trace};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use toml_edit::DocumentMut;
+
+use crate::renderer::ThemeConfig;
+use crate::types::{GameMode, LogLevel, QuizDirection, SchedulerAlgorithm};
+use crate::utils::database::{CsvDialect, TagFilter};
 
-use crate::types::LogLevel;
+pub use field::{ConfigField, FIELDS, field_index};
 
 pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Config> {
-    let mut config = Config::build(path)?;
+    let mut config = Config::build(&path)?;
     config.parse()?;
+    config.path = path.as_ref().to_path_buf();
 
     debug!("Configuration loaded: {:?}", config);
     Ok(config)
 }
 
+/// Writes the configuration back to the TOML file it was loaded from.
+///
+/// Only the values are updated, so keys unknown to `Config`, comments and formatting stay
+/// as the user wrote them. Keys of values that were cleared are removed. The file is
+/// replaced atomically and the previous version is kept next to it with a `.bak` extension.
+pub fn save(config: &Config) -> anyhow::Result<()> {
+    let mut config = config.clone();
+    config.parse()?;
+
+    let path = &config.path;
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read configuration file {:?}", path))?;
+    let mut document: DocumentMut = content
+        .parse()
+        .context("Failed to parse configuration file")?;
+    let updated =
+        toml_edit::ser::to_document(&config).context("Failed to serialize configuration")?;
+    let known = toml_edit::ser::to_document(&Config::build(path)?)
+        .context("Failed to serialize configuration")?;
+
+    merge_table(
+        document.as_table_mut(),
+        updated.as_table(),
+        known.as_table(),
+    );
+
+    let tmp_path = path.with_extension("toml.tmp");
+    let mut file =
+        File::create(&tmp_path).with_context(|| format!("Failed to create {:?}", tmp_path))?;
+    file.write_all(document.to_string().as_bytes())?;
+    file.sync_all()?;
+    fs::copy(path, path.with_extension("toml.bak"))
+        .context("Failed to back up configuration file")?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to replace configuration file {:?}", path))?;
+
+    debug!("Configuration saved to {:?}", path);
+    Ok(())
+}
+
+/// Copies values of `updated` into `table`, keeping the decor of existing keys and tables.
+/// Nested tables are written as standard tables rather than inline ones. Keys `Config`
+/// read from the file, as listed in `known`, are removed if they're missing in `updated`.
+fn merge_table(table: &mut toml_edit::Table, updated: &toml_edit::Table, known: &toml_edit::Table) {
+    table.retain(|key, _| updated.contains_key(key) || !known.contains_key(key));
+    for (key, item) in updated.iter() {
+        let item = match item {
+            toml_edit::Item::Value(toml_edit::Value::InlineTable(inline)) => {
+                toml_edit::Item::Table(inline.clone().into_table())
+            }
+            item => item.clone(),
+        };
+        match (table.get_mut(key), item) {
+            (Some(toml_edit::Item::Value(existing)), toml_edit::Item::Value(value)) => {
+                let decor = existing.decor().clone();
+                *existing = value;
+                *existing.decor_mut() = decor;
+            }
+            (Some(toml_edit::Item::Table(existing)), toml_edit::Item::Table(updated)) => {
+                let known = match known.get(key) {
+                    Some(toml_edit::Item::Value(toml_edit::Value::InlineTable(inline))) => {
+                        inline.clone().into_table()
+                    }
+                    Some(toml_edit::Item::Table(known)) => known.clone(),
+                    _ => toml_edit::Table::new(),
+                };
+                merge_table(existing, &updated, &known);
+            }
+            (_, item) => table[key] = item,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
+    /// File the configuration was loaded from and is saved to.
+    #[serde(skip)]
+    pub path: PathBuf,
+
     pub db_conn_string: String,
 
     pub log_level: LogLevel,
@@ -22,6 +109,52 @@ pub struct Config {
 
     pub input_box_width: usize,
     pub phrases_per_round: usize,
+    #[serde(default)]
+    pub quiz_direction: QuizDirection,
+    #[serde(default)]
+    pub game_mode: GameMode,
+    #[serde(default = "default_choices_count")]
+    pub choices_count: usize,
+    /// Seconds to answer each phrase in the timed game mode.
+    #[serde(default = "default_time_limit")]
+    pub time_limit: u64,
+    /// Deck practised in games, all decks if not set. Remembered by the deck picker.
+    #[serde(default)]
+    pub deck: Option<String>,
+    /// Tag filter expression selecting phrases practised in games, see `TagFilter`.
+    #[serde(default)]
+    pub tag_filter: Option<String>,
+    /// Tag filter given on the command line, used instead of `tag_filter` for this session
+    /// only and never saved. Cleared once another filter is chosen in the app.
+    #[serde(skip)]
+    pub session_tag_filter: Option<String>,
+
+    #[serde(default)]
+    pub scheduler: SchedulerAlgorithm,
+
+    #[serde(default = "default_answer_tolerance")]
+    pub answer_tolerance: f64,
+    #[serde(default)]
+    pub fold_diacritics: bool,
+
+    /// Layout of CSV phrase files, the `[csv]` table.
+    #[serde(default)]
+    pub csv: CsvDialect,
+    /// Colours of the screens, the `[theme]` table.
+    #[serde(default)]
+    pub theme: ThemeConfig,
+}
+
+fn default_choices_count() -> usize {
+    4
+}
+
+fn default_time_limit() -> u64 {
+    15
+}
+
+fn default_answer_tolerance() -> f64 {
+    0.1
 }
 
 impl Config {
@@ -37,24 +170,26 @@ impl Config {
     }
 
     fn parse(&mut self) -> anyhow::Result<()> {
-        self.sanitize_string(&self.db_conn_string)?;
-
-        if let Some(ref log_dir_uri) = self.log_dir_uri {
-            self.sanitize_string(log_dir_uri)?;
-        }
-
-        if self.phrases_per_round == 0 {
-            anyhow::bail!("Phrases per round must be greater than zero.");
-        }
-
-        if self.input_box_width < 30 {
-            anyhow::bail!("Input box width must be greater than or equal to 30.");
+        for field in FIELDS {
+            field.validate(self)?;
         }
 
         trace!("Configuration parsed");
         Ok(())
     }
 
+    /// Tag filter in effect, the one given on the command line if any.
+    pub fn active_tag_filter(&self) -> Option<&str> {
+        self.session_tag_filter
+            .as_deref()
+            .or(self.tag_filter.as_deref())
+    }
+
+    /// Parses the tag filter in effect, matching every phrase if it's not set.
+    pub fn parse_tag_filter(&self) -> anyhow::Result<TagFilter> {
+        self.active_tag_filter().unwrap_or_default().parse()
+    }
+
     fn sanitize_string(&self, value: &str) -> anyhow::Result<()> {
         // TODO implement sanitization logic
 
@@ -103,4 +238,103 @@ db_conn_string = "file:///path/to/db.csv"
         let error_msg = result.unwrap_err().to_string();
         assert!(error_msg.contains("Failed to deserialize configuration"));
     }
+
+    #[test]
+    fn test_save_keeps_comments_and_unknown_keys() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("config.toml");
+        let example = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config.example.toml");
+        let content = "custom_key = \"kept\"\n".to_string() + &fs::read_to_string(example).unwrap();
+        fs::write(&path, &content).unwrap();
+
+        let mut config = load(&path).expect("Failed to load configuration");
+        config.phrases_per_round = 42;
+        config.game_mode = GameMode::MultipleChoice;
+        config.csv.delimiter = ';';
+        save(&config).expect("Failed to save configuration");
+
+        let saved = fs::read_to_string(&path).unwrap();
+        assert!(saved.contains("phrases_per_round = 42"));
+        assert!(saved.contains("game_mode = \"multiple_choice\""));
+        assert!(saved.contains("custom_key = \"kept\""));
+        assert!(saved.contains("delimiter = \";\""));
+        let comments = |text: &str| text.lines().filter(|line| line.starts_with('#')).count();
+        assert_eq!(comments(&saved), comments(&content));
+        assert_eq!(
+            fs::read_to_string(path.with_extension("toml.bak")).unwrap(),
+            content
+        );
+        assert_eq!(load(&path).unwrap().phrases_per_round, 42);
+    }
+
+    #[test]
+    fn test_save_timed_mode_with_time_limit() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("config.toml");
+        let example = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config.example.toml");
+        fs::copy(example, &path).unwrap();
+
+        let mut config = load(&path).expect("Failed to load configuration");
+        config.game_mode = GameMode::Timed;
+        config.time_limit = 30;
+        save(&config).expect("Failed to save configuration");
+
+        let saved = load(&path).unwrap();
+        assert_eq!(saved.game_mode, GameMode::Timed);
+        assert_eq!(saved.time_limit, 30);
+    }
+
+    #[test]
+    fn test_session_tag_filter_is_not_saved() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("config.toml");
+        let example = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config.example.toml");
+        fs::copy(example, &path).unwrap();
+
+        let mut config = load(&path).expect("Failed to load configuration");
+        config.tag_filter = Some("idiom".to_string());
+        config.session_tag_filter = Some("news -formal".to_string());
+        assert_eq!(config.active_tag_filter(), Some("news -formal"));
+        save(&config).expect("Failed to save configuration");
+
+        let saved = load(&path).unwrap();
+        assert_eq!(saved.tag_filter.as_deref(), Some("idiom"));
+        assert_eq!(saved.session_tag_filter, None);
+        assert!(
+            !fs::read_to_string(&path)
+                .unwrap()
+                .contains("news -formal\"")
+        );
+    }
+
+    #[test]
+    fn test_save_keeps_cleared_values_cleared() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("config.toml");
+        let example = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config.example.toml");
+        fs::copy(example, &path).unwrap();
+
+        let mut config = load(&path).expect("Failed to load configuration");
+        config.theme.file = Some("theme.toml".to_string());
+        save(&config).expect("Failed to save configuration");
+        assert!(
+            fs::read_to_string(&path)
+                .unwrap()
+                .contains("file = \"theme.toml\"")
+        );
+
+        config.theme.file = None;
+        assert_eq!(config.csv.tags_column, Some(3));
+        FIELDS[field_index('y').unwrap()]
+            .update(&mut config, "")
+            .unwrap();
+        save(&config).expect("Failed to save configuration");
+
+        let saved = load(&path).unwrap();
+        assert_eq!(saved.csv.tags_column, None);
+        assert_eq!(saved.theme.file, None);
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("tags_column = 0"));
+        assert!(!content.contains("file = \"theme.toml\""));
+    }
 }
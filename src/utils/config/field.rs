@@ -0,0 +1,495 @@
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde::de::value::{Error as ValueError, StrDeserializer};
+
+use super::Config;
+
+use crate::renderer::THEMES;
+
+/// Kind of value a setting holds, deciding how it's edited in settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldKind {
+    Text,
+    Integer,
+    Decimal,
+    Flag,
+    /// One of the listed values, as written in the config file.
+    Choice(&'static [&'static str]),
+}
+
+/// Descriptor of a single `Config` field.
+///
+/// The settings editor and `Config::parse` are both driven by `FIELDS`, so a new setting
+/// only needs an entry there.
+pub struct ConfigField {
+    /// Key selecting the field in the settings menu.
+    pub key: char,
+    /// Name of the key in the config file.
+    pub name: &'static str,
+    pub label: &'static str,
+    pub help: &'static str,
+    pub kind: FieldKind,
+    get: fn(&Config) -> String,
+    set: fn(&mut Config, &str) -> anyhow::Result<()>,
+    validate: fn(&Config) -> anyhow::Result<()>,
+}
+
+impl ConfigField {
+    /// Returns the field's value formatted as it's written in the config file.
+    pub fn value(&self, config: &Config) -> String {
+        (self.get)(config)
+    }
+
+    /// Parses and validates `value`, leaving `config` untouched if it's not accepted.
+    pub fn update(&self, config: &mut Config, value: &str) -> anyhow::Result<()> {
+        let mut updated = config.clone();
+        (self.set)(&mut updated, value.trim())?;
+        (self.validate)(&updated)?;
+        *config = updated;
+        Ok(())
+    }
+
+    pub fn validate(&self, config: &Config) -> anyhow::Result<()> {
+        (self.validate)(config)
+    }
+
+    /// Returns the value following the current one for fields changed by cycling through
+    /// their options, `None` for fields the user types in.
+    pub fn next_value(&self, config: &Config) -> Option<String> {
+        let current = self.value(config);
+        match self.kind {
+            FieldKind::Flag => Some((current != "true").to_string()),
+            FieldKind::Choice(options) => {
+                let idx = options.iter().position(|option| *option == current);
+                let next = idx.map_or(0, |idx| (idx + 1) % options.len());
+                Some(options[next].to_string())
+            }
+            FieldKind::Text | FieldKind::Integer | FieldKind::Decimal => None,
+        }
+    }
+}
+
+/// Returns the index in `FIELDS` of the field selected with `key`.
+pub fn field_index(key: char) -> Option<usize> {
+    FIELDS.iter().position(|field| field.key == key)
+}
+
+pub static FIELDS: &[ConfigField] = &[
+    ConfigField {
+        key: 'p',
+        name: "phrases_per_round",
+        label: "Phrases per round",
+        help: "Number of phrases practised in a single round",
+        kind: FieldKind::Integer,
+        get: |config| config.phrases_per_round.to_string(),
+        set: |config, value| {
+            config.phrases_per_round = parse_value(value)?;
+            Ok(())
+        },
+        validate: |config| {
+            if config.phrases_per_round == 0 {
+                anyhow::bail!("Phrases per round must be greater than zero.");
+            }
+            Ok(())
+        },
+    },
+    ConfigField {
+        key: 'd',
+        name: "quiz_direction",
+        label: "Quiz direction",
+        help: "Ask for the translation, the original or either of them",
+        kind: FieldKind::Choice(&["forward", "reverse", "random"]),
+        get: |config| choice_name(&config.quiz_direction),
+        set: |config, value| {
+            config.quiz_direction = parse_choice(value)?;
+            Ok(())
+        },
+        validate: |_| Ok(()),
+    },
+    ConfigField {
+        key: 'g',
+        name: "game_mode",
+        label: "Game mode",
+        help: "Type answers, pick them from several candidates or race against the clock",
+        kind: FieldKind::Choice(&["typed", "multiple_choice", "timed"]),
+        get: |config| choice_name(&config.game_mode),
+        set: |config, value| {
+            config.game_mode = parse_choice(value)?;
+            Ok(())
+        },
+        validate: |_| Ok(()),
+    },
+    ConfigField {
+        key: 'n',
+        name: "choices_count",
+        label: "Number of choices",
+        help: "Candidates shown in multiple choice mode, between 2 and 9",
+        kind: FieldKind::Integer,
+        get: |config| config.choices_count.to_string(),
+        set: |config, value| {
+            config.choices_count = parse_value(value)?;
+            Ok(())
+        },
+        validate: |config| {
+            if !(2..=9).contains(&config.choices_count) {
+                anyhow::bail!("Choices count must be between 2 and 9.");
+            }
+            Ok(())
+        },
+    },
+    ConfigField {
+        key: 'i',
+        name: "time_limit",
+        label: "Time limit",
+        help: "Seconds to answer each phrase in timed mode, between 3 and 300",
+        kind: FieldKind::Integer,
+        get: |config| config.time_limit.to_string(),
+        set: |config, value| {
+            config.time_limit = parse_value(value)?;
+            Ok(())
+        },
+        validate: |config| {
+            if !(3..=300).contains(&config.time_limit) {
+                anyhow::bail!("Time limit must be between 3 and 300 seconds.");
+            }
+            Ok(())
+        },
+    },
+    ConfigField {
+        key: 'a',
+        name: "scheduler",
+        label: "Scheduler",
+        help: "Spaced-repetition algorithm, applied when the next game starts",
+        kind: FieldKind::Choice(&["sm2", "fsrs"]),
+        get: |config| choice_name(&config.scheduler),
+        set: |config, value| {
+            config.scheduler = parse_choice(value)?;
+            Ok(())
+        },
+        validate: |_| Ok(()),
+    },
+    ConfigField {
+        key: 't',
+        name: "answer_tolerance",
+        label: "Answer tolerance",
+        help: "Accepted ratio of typos to answer length, up to 0.5",
+        kind: FieldKind::Decimal,
+        get: |config| config.answer_tolerance.to_string(),
+        set: |config, value| {
+            config.answer_tolerance = parse_value(value)?;
+            Ok(())
+        },
+        validate: |config| {
+            if !(0.0..=0.5).contains(&config.answer_tolerance) {
+                anyhow::bail!("Answer tolerance must be between 0.0 and 0.5.");
+            }
+            Ok(())
+        },
+    },
+    ConfigField {
+        key: 'f',
+        name: "fold_diacritics",
+        label: "Fold diacritics",
+        help: "Treat letters with diacritics as their base letters",
+        kind: FieldKind::Flag,
+        get: |config| config.fold_diacritics.to_string(),
+        set: |config, value| {
+            config.fold_diacritics = parse_value(value)?;
+            Ok(())
+        },
+        validate: |_| Ok(()),
+    },
+    ConfigField {
+        key: 'e',
+        name: "tag_filter",
+        label: "Tag filter",
+        help: "Tags of practised phrases, e.g. 'news idiom | business -formal'",
+        kind: FieldKind::Text,
+        get: |config| config.tag_filter.clone().unwrap_or_default(),
+        set: |config, value| {
+            config.tag_filter = Some(value.to_string()).filter(|value| !value.is_empty());
+            config.session_tag_filter = None;
+            Ok(())
+        },
+        validate: |config| config.parse_tag_filter().map(|_| ()),
+    },
+    ConfigField {
+        key: 'w',
+        name: "input_box_width",
+        label: "Input box width",
+        help: "Width of the answer input box, at least 30, shrunk in narrower terminals",
+        kind: FieldKind::Integer,
+        get: |config| config.input_box_width.to_string(),
+        set: |config, value| {
+            config.input_box_width = parse_value(value)?;
+            Ok(())
+        },
+        validate: |config| {
+            if config.input_box_width < 30 {
+                anyhow::bail!("Input box width must be greater than or equal to 30.");
+            }
+            Ok(())
+        },
+    },
+    ConfigField {
+        key: 'c',
+        name: "db_conn_string",
+        label: "Database",
+        help: "file://path/to/phrases.csv or sqlite://path/to/phrases.db",
+        kind: FieldKind::Text,
+        get: |config| config.db_conn_string.clone(),
+        set: |config, value| {
+            config.db_conn_string = value.to_string();
+            Ok(())
+        },
+        validate: |config| {
+            if config.db_conn_string.is_empty() {
+                anyhow::bail!("Database connection string must not be empty.");
+            }
+            config.sanitize_string(&config.db_conn_string)
+        },
+    },
+    ConfigField {
+        key: 'l',
+        name: "log_level",
+        label: "Log level",
+        help: "Verbosity of the log file, applied on the next start",
+        kind: FieldKind::Choice(&["off", "error", "warn", "info", "debug", "trace"]),
+        get: |config| choice_name(&config.log_level),
+        set: |config, value| {
+            config.log_level = parse_choice(value)?;
+            Ok(())
+        },
+        validate: |_| Ok(()),
+    },
+    ConfigField {
+        key: 'h',
+        name: "theme.name",
+        label: "Theme",
+        help: "Colours of the screens, NO_COLOR turns them off",
+        kind: FieldKind::Choice(&THEMES),
+        get: |config| config.theme.name.clone(),
+        set: |config, value| {
+            config.theme.name = value.to_string();
+            Ok(())
+        },
+        validate: |config| config.theme.validate(),
+    },
+    ConfigField {
+        key: 'o',
+        name: "log_dir_uri",
+        label: "Log directory",
+        help: "file://path/to/logs/ or empty for stderr, applied on the next start",
+        kind: FieldKind::Text,
+        get: |config| config.log_dir_uri.clone().unwrap_or_default(),
+        set: |config, value| {
+            config.log_dir_uri = Some(value.to_string()).filter(|value| !value.is_empty());
+            Ok(())
+        },
+        validate: |config| match config.log_dir_uri {
+            Some(ref log_dir_uri) => config.sanitize_string(log_dir_uri),
+            None => Ok(()),
+        },
+    },
+    ConfigField {
+        key: 'm',
+        name: "csv.delimiter",
+        label: "CSV delimiter",
+        help: "Character separating columns of CSV phrase files, 'tab' for tabs",
+        kind: FieldKind::Text,
+        get: |config| format_character(config.csv.delimiter),
+        set: |config, value| {
+            config.csv.delimiter = parse_character(value)?;
+            Ok(())
+        },
+        validate: |config| config.csv.validate_characters(),
+    },
+    ConfigField {
+        key: 'u',
+        name: "csv.quote",
+        label: "CSV quote",
+        help: "Character quoting fields of CSV phrase files",
+        kind: FieldKind::Text,
+        get: |config| format_character(config.csv.quote),
+        set: |config, value| {
+            config.csv.quote = parse_character(value)?;
+            Ok(())
+        },
+        validate: |config| config.csv.validate_characters(),
+    },
+    ConfigField {
+        key: 'r',
+        name: "csv.has_headers",
+        label: "CSV header row",
+        help: "Whether the first row of CSV phrase files names the languages",
+        kind: FieldKind::Flag,
+        get: |config| config.csv.has_headers.to_string(),
+        set: |config, value| {
+            config.csv.has_headers = parse_value(value)?;
+            Ok(())
+        },
+        validate: |_| Ok(()),
+    },
+    ConfigField {
+        key: 'v',
+        name: "csv.encoding",
+        label: "CSV encoding",
+        help: "Encoding of CSV phrase files, e.g. 'utf-8' or 'windows-1250'",
+        kind: FieldKind::Text,
+        get: |config| config.csv.encoding.clone(),
+        set: |config, value| {
+            config.csv.encoding = value.to_string();
+            Ok(())
+        },
+        validate: |config| config.csv.validate_encoding(),
+    },
+    ConfigField {
+        key: 'j',
+        name: "csv.original_column",
+        label: "Original column",
+        help: "Column of CSV phrase files holding the phrase, numbered from 1",
+        kind: FieldKind::Integer,
+        get: |config| config.csv.original_column.to_string(),
+        set: |config, value| {
+            config.csv.original_column = parse_value(value)?;
+            Ok(())
+        },
+        validate: |config| config.csv.validate_columns(),
+    },
+    ConfigField {
+        key: 'k',
+        name: "csv.translation_column",
+        label: "Translation column",
+        help: "Column of CSV phrase files holding the translations, numbered from 1",
+        kind: FieldKind::Integer,
+        get: |config| config.csv.translation_column.to_string(),
+        set: |config, value| {
+            config.csv.translation_column = parse_value(value)?;
+            Ok(())
+        },
+        validate: |config| config.csv.validate_columns(),
+    },
+    ConfigField {
+        key: 'y',
+        name: "csv.tags_column",
+        label: "Tags column",
+        help: "Column of CSV phrase files holding the tags, empty if there's none",
+        kind: FieldKind::Integer,
+        get: |config| optional_value(config.csv.tags_column),
+        set: |config, value| {
+            config.csv.tags_column = parse_optional_value(value)?;
+            Ok(())
+        },
+        validate: |config| config.csv.validate_columns(),
+    },
+    ConfigField {
+        key: 'z',
+        name: "csv.notes_column",
+        label: "Notes column",
+        help: "Column of CSV phrase files holding the notes, empty if there's none",
+        kind: FieldKind::Integer,
+        get: |config| optional_value(config.csv.notes_column),
+        set: |config, value| {
+            config.csv.notes_column = parse_optional_value(value)?;
+            Ok(())
+        },
+        validate: |config| config.csv.validate_columns(),
+    },
+];
+
+fn parse_value<T: std::str::FromStr>(value: &str) -> anyhow::Result<T> {
+    value
+        .parse()
+        .map_err(|_| anyhow::anyhow!("'{}' is not a valid value.", value))
+}
+
+fn parse_optional_value<T: std::str::FromStr>(value: &str) -> anyhow::Result<Option<T>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    parse_value(value).map(Some)
+}
+
+fn optional_value<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Parses a single character, accepting `tab` as tabs can't be typed in settings.
+fn parse_character(value: &str) -> anyhow::Result<char> {
+    match value {
+        "tab" | "\\t" => Ok('\t'),
+        _ => parse_value(value),
+    }
+}
+
+fn format_character(c: char) -> String {
+    match c {
+        '\t' => "tab".to_string(),
+        _ => c.to_string(),
+    }
+}
+
+fn parse_choice<T: DeserializeOwned>(value: &str) -> anyhow::Result<T> {
+    T::deserialize(StrDeserializer::<ValueError>::new(value))
+        .map_err(|_| anyhow::anyhow!("'{}' is not a valid option.", value))
+}
+
+fn choice_name<T: Serialize>(value: &T) -> String {
+    value
+        .serialize(toml_edit::ser::ValueSerializer::new())
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_fields_have_unique_keys() {
+        let keys: HashSet<char> = FIELDS.iter().map(|field| field.key).collect();
+        assert_eq!(keys.len(), FIELDS.len());
+        assert!(!keys.contains(&'s') && !keys.contains(&'b'));
+    }
+
+    fn find_field(key: char) -> &'static ConfigField {
+        &FIELDS[field_index(key).unwrap()]
+    }
+
+    #[test]
+    fn test_update_validates_and_cycles_values() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("config.example.toml");
+        let mut config = super::super::load(path).unwrap();
+
+        let field = find_field('n');
+        assert!(field.update(&mut config, "12").is_err());
+        assert!(field.update(&mut config, "abc").is_err());
+        field.update(&mut config, " 6 ").unwrap();
+        assert_eq!(config.choices_count, 6);
+
+        let field = find_field('l');
+        assert_eq!(field.value(&config), "warn");
+        let next = field.next_value(&config).unwrap();
+        field.update(&mut config, &next).unwrap();
+        assert_eq!(field.value(&config), "info");
+
+        let field = find_field('o');
+        field.update(&mut config, "").unwrap();
+        assert_eq!(config.log_dir_uri, None);
+
+        let field = find_field('y');
+        assert!(field.update(&mut config, "1").is_err());
+        field.update(&mut config, "").unwrap();
+        assert_eq!(config.csv.tags_column, None);
+        let field = find_field('m');
+        assert!(field.update(&mut config, "\"").is_err());
+        field.update(&mut config, "tab").unwrap();
+        assert_eq!(
+            (config.csv.delimiter, field.value(&config)),
+            ('\t', "tab".to_string())
+        );
+    }
+}
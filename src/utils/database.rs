@@ -1,68 +1,604 @@
+mod anki;
+mod csv_dialect;
+mod csv_storage;
+mod phrase;
+mod sqlite_storage;
+mod tag_filter;
+
 use anyhow::Context;
+use chrono::{DateTime, Local};
 use log::{debug, trace};
-use rand::seq::SliceRandom;
-use std::fs::File;
+use rand::seq::IndexedRandom;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::types::Direction;
+
+use csv_storage::CsvStorage;
+use sqlite_storage::SqliteStorage;
+
+pub use csv_dialect::CsvDialect;
+pub use phrase::{Phrase, PhraseId, Phrases, split_tags, split_translations};
+pub use tag_filter::TagFilter;
 
-pub type OriginalSentence = String;
-pub type Translation = String;
-pub type Phrase = (OriginalSentence, Translation); // TODO change to struct
-pub type Phrases = Vec<Phrase>;
+/// Outcome of a single phrase practised during a round.
+#[derive(Debug, Clone)]
+pub struct Review {
+    pub phrase: Phrase,
+    pub direction: Direction,
+    pub attempts: usize,
+    pub recognized: bool,
+    /// Number of hints revealed before answering.
+    pub hints: usize,
+    pub reviewed_at: DateTime<Local>,
+}
+
+/// Backend persisting phrases and review history.
+pub trait Storage {
+    fn load_phrases(&self) -> anyhow::Result<Phrases>;
+    /// Adds phrases that are not stored yet and returns how many were added.
+    fn insert_phrases(&mut self, phrases: &Phrases) -> anyhow::Result<usize>;
+    /// Removes the phrase and returns whether it existed.
+    fn remove_phrase(&mut self, id: PhraseId) -> anyhow::Result<bool>;
+    /// Returns the whole review history, oldest first.
+    fn load_reviews(&self) -> anyhow::Result<Vec<Review>>;
+    fn save_reviews(&mut self, reviews: &[Review]) -> anyhow::Result<()>;
+    /// Returns when the stored phrases were last modified, `None` if it can't be told apart
+    /// from changes to the review history.
+    fn phrases_modified_at(&self) -> anyhow::Result<Option<SystemTime>>;
+}
 
 pub struct Database {
+    storage: Box<dyn Storage>,
     records: Phrases,
+    /// Dialect of the CSV phrase files, also used for importing and exporting CSV files.
+    csv_dialect: CsvDialect,
+    /// Modification time of the phrases when `records` were loaded.
+    modified_at: Option<SystemTime>,
 }
 
 impl Database {
-    pub fn new(conn_string: &str) -> anyhow::Result<Self> {
-        // TODO use SQLite, divide per language, include metadata, etc.
-        let filepath = conn_string
-            .strip_prefix("file://")
-            .context("Failed to parse database connection string")?;
-        trace!(
-            "Database connection string parsed, loading from file: {}",
-            filepath
-        );
-        let records = Database::from_csv(filepath)?;
+    /// Opens the storage pointed to by the connection string.
+    ///
+    /// Supported schemes:
+    /// - `file://path/to/phrases.csv` - flat CSV file with review history kept next to it
+    /// - `sqlite://path/to/phrases.db` - SQLite database, created and migrated on first open
+    pub fn new(conn_string: &str, csv_dialect: &CsvDialect) -> anyhow::Result<Self> {
+        let storage: Box<dyn Storage> = if let Some(path) = conn_string.strip_prefix("file://") {
+            trace!(
+                "Database connection string parsed, loading from file: {}",
+                path
+            );
+            Box::new(CsvStorage::open(path, csv_dialect)?)
+        } else if let Some(path) = conn_string.strip_prefix("sqlite://") {
+            trace!(
+                "Database connection string parsed, opening SQLite: {}",
+                path
+            );
+            Box::new(SqliteStorage::open(path)?)
+        } else {
+            anyhow::bail!(
+                "Failed to parse database connection string: {}",
+                conn_string
+            );
+        };
+
+        let modified_at = storage.phrases_modified_at()?;
+        let records = storage.load_phrases()?;
         debug!(
             "Database loaded from {} with {} records",
             conn_string,
             records.len()
         );
-        Ok(Database { records })
+        Ok(Database {
+            storage,
+            records,
+            csv_dialect: csv_dialect.clone(),
+            modified_at,
+        })
     }
 
-    pub fn get_phrases(&self, limit: usize) -> Phrases {
-        let mut all_phrases = self.records.clone();
-        // TODO check randomness of this solution
-        let mut random_generator = rand::rng();
-        all_phrases.shuffle(&mut random_generator);
-        trace!("Shuffled records for randomness");
+    /// Loads the phrases again if they were changed on disk, e.g. by a teammate editing the
+    /// shared file, and returns whether they're different now.
+    ///
+    /// Storages that can't tell when their phrases were modified are always read again.
+    pub fn reload_if_changed(&mut self) -> anyhow::Result<bool> {
+        let modified_at = self.storage.phrases_modified_at()?;
+        if modified_at.is_some() && modified_at == self.modified_at {
+            trace!("Phrases not modified since {:?}", modified_at);
+            return Ok(false);
+        }
 
-        let phrases: Phrases = all_phrases.iter().take(limit).cloned().collect();
-        trace!("Fetched {} random records from database", phrases.len());
+        let records = self.storage.load_phrases()?;
+        self.modified_at = modified_at;
+        if records == self.records {
+            trace!("Phrases reloaded, no changes found");
+            return Ok(false);
+        }
+        debug!(
+            "Phrases changed on disk, reloaded {} records (previously {})",
+            records.len(),
+            self.records.len()
+        );
+        self.records = records;
+        Ok(true)
+    }
+
+    /// Returns when the phrases behind the connection string were last modified, without
+    /// opening the storage. `None` if the storage can't tell, like SQLite databases.
+    pub fn phrases_modified_at(conn_string: &str) -> anyhow::Result<Option<SystemTime>> {
+        match conn_string.strip_prefix("file://") {
+            Some(path) => csv_storage::phrases_modified_at(Path::new(path)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_phrases(&self) -> &Phrases {
+        trace!("Fetched {} records from database", self.records.len());
+        &self.records
+    }
+
+    /// Returns phrases of the given deck, or of all decks when no deck is given, whose tags
+    /// match the filter.
+    pub fn get_filtered_phrases(&self, deck: Option<&str>, tags: &TagFilter) -> Phrases {
+        let phrases: Phrases = self
+            .records
+            .iter()
+            .filter(|record| deck.is_none() || record.deck.as_deref() == deck)
+            .filter(|record| tags.matches(&record.tags))
+            .cloned()
+            .collect();
+        trace!(
+            "Fetched {} records of deck {:?} matching {:?}",
+            phrases.len(),
+            deck,
+            tags
+        );
         phrases
     }
 
-    // TODO use it as tool to read new data into DB
-    fn from_csv(path: &str) -> anyhow::Result<Phrases> {
-        let mut records = Vec::new();
-        let file = File::open(path)?;
-        let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(file);
-        trace!("CSV reader initialized for file: {}", path);
-
-        for result in reader.records() {
-            let record = result?;
-            if record.len() == 2 {
-                let key = record[0].to_string();
-                let value = record[1].to_string();
-                records.push((key, value));
-                trace!("Row added: {:?}", record);
-            } else {
-                trace!("Row skipped: {:?}", record);
-            }
+    /// Returns names of all decks together with their number of phrases, sorted by name.
+    pub fn get_decks(&self) -> Vec<(String, usize)> {
+        let mut decks: BTreeMap<&str, usize> = BTreeMap::new();
+        for deck in self
+            .records
+            .iter()
+            .filter_map(|record| record.deck.as_deref())
+        {
+            *decks.entry(deck).or_default() += 1;
+        }
+        decks
+            .into_iter()
+            .map(|(deck, count)| (deck.to_string(), count))
+            .collect()
+    }
+
+    /// Picks up to `count` wrong answers for the phrase asked in the given direction,
+    /// drawn at random from the other records.
+    pub fn get_distractors(
+        &self,
+        phrase: &Phrase,
+        direction: Direction,
+        count: usize,
+    ) -> Vec<String> {
+        let accepted = phrase.answers(direction);
+        let mut candidates: Vec<&str> = self
+            .records
+            .iter()
+            .filter(|record| record.id != phrase.id)
+            .filter_map(|record| record.answers(direction).first())
+            .map(String::as_str)
+            .filter(|candidate| !accepted.iter().any(|answer| answer == candidate))
+            .collect();
+        candidates.sort_unstable();
+        candidates.dedup();
+
+        let distractors: Vec<String> = candidates
+            .choose_multiple(&mut rand::rng(), count)
+            .map(|candidate| candidate.to_string())
+            .collect();
+        trace!(
+            "Picked {} distractors for phrase {}",
+            distractors.len(),
+            phrase.id
+        );
+        distractors
+    }
+
+    pub fn get_reviews(&self) -> anyhow::Result<Vec<Review>> {
+        self.storage
+            .load_reviews()
+            .context("Failed to load review history")
+    }
+
+    /// Reads phrases from a CSV file and stores the ones not present yet.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of newly added phrases
+    /// * `Err` - If the file can't be read or the storage rejects the phrases
+    pub fn import_csv<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<usize> {
+        let phrases = csv_storage::read_phrases(path.as_ref(), &self.csv_dialect)?;
+        self.import_phrases(&phrases, path.as_ref())
+    }
+
+    /// Reads notes from an Anki package (`.apkg`, `.colpkg`) or plain text export and stores
+    /// the ones not present yet.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of newly added phrases
+    /// * `Err` - If the file can't be read or the storage rejects the phrases
+    pub fn import_anki<P: AsRef<Path>>(&mut self, path: P) -> anyhow::Result<usize> {
+        let phrases = anki::read_notes(path.as_ref())?;
+        self.import_phrases(&phrases, path.as_ref())
+    }
+
+    fn import_phrases(&mut self, phrases: &Phrases, path: &Path) -> anyhow::Result<usize> {
+        let inserted = self
+            .storage
+            .insert_phrases(phrases)
+            .context("Failed to import phrases")?;
+        self.records = self.storage.load_phrases()?;
+
+        debug!(
+            "Imported {} of {} phrases from {:?}",
+            inserted,
+            phrases.len(),
+            path
+        );
+        Ok(inserted)
+    }
+
+    /// Adds a single phrase unless it's stored already.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` - The phrase was added
+    /// * `Ok(false)` - The same phrase is stored already
+    /// * `Err` - If the storage rejects the phrase
+    pub fn add_phrase(&mut self, phrase: Phrase) -> anyhow::Result<bool> {
+        let inserted = self.storage.insert_phrases(&vec![phrase])?;
+        self.records = self.storage.load_phrases()?;
+        Ok(inserted > 0)
+    }
+
+    /// Removes the phrase with the given id.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(true)` - The phrase was removed
+    /// * `Ok(false)` - No phrase with such id exists
+    /// * `Err` - If the storage can't be updated
+    pub fn remove_phrase(&mut self, id: PhraseId) -> anyhow::Result<bool> {
+        let removed = self.storage.remove_phrase(id)?;
+        self.records = self.storage.load_phrases()?;
+        debug!("Phrase {} removed: {}", id, removed);
+        Ok(removed)
+    }
+
+    /// Writes all phrases to a CSV file that can be imported back.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of exported phrases
+    /// * `Err` - If the file can't be written
+    pub fn export_csv<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<usize> {
+        csv_storage::write_phrases(path.as_ref(), &self.records, &self.csv_dialect)?;
+        debug!(
+            "Exported {} phrases to {:?}",
+            self.records.len(),
+            path.as_ref()
+        );
+        Ok(self.records.len())
+    }
+
+    /// Writes all phrases to an Anki plain text file that can be imported as notes.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(usize)` - Number of exported phrases
+    /// * `Err` - If the file can't be written
+    pub fn export_anki<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<usize> {
+        anki::write_notes(path.as_ref(), &self.records)?;
+        debug!(
+            "Exported {} notes to {:?}",
+            self.records.len(),
+            path.as_ref()
+        );
+        Ok(self.records.len())
+    }
+
+    /// Appends round results to the review history.
+    pub fn save_reviews(&mut self, reviews: &[Review]) -> anyhow::Result<()> {
+        if reviews.is_empty() {
+            trace!("No reviews to save");
+            return Ok(());
+        }
+
+        self.storage.save_reviews(reviews)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::io::Write;
+
+    fn create_csv(content: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::Builder::new()
+            .suffix(".csv")
+            .tempfile()
+            .expect("Failed to create temp file");
+        file.write_all(content.as_bytes())
+            .expect("Failed to write to temp file");
+        file
+    }
+
+    fn review(phrase: &Phrase) -> Review {
+        Review {
+            phrase: phrase.clone(),
+            direction: Direction::Reverse,
+            attempts: 2,
+            recognized: true,
+            hints: 1,
+            reviewed_at: Local::now(),
         }
+    }
 
-        trace!("Total records loaded from CSV: {}", records.len());
-        Ok(records)
+    #[test]
+    fn test_new_with_unknown_scheme() {
+        let result = Database::new("postgres://localhost/phrasey", &CsvDialect::default());
+
+        assert!(result.is_err());
+        let error_msg = result.err().unwrap().to_string();
+        assert!(error_msg.contains("Failed to parse database connection string"));
+    }
+
+    #[test]
+    fn test_split_translations() {
+        assert_eq!(
+            split_translations("Issue an appeal | Make an appeal|"),
+            vec!["Issue an appeal", "Make an appeal"]
+        );
+        assert!(split_translations(" | ").is_empty());
+    }
+
+    #[test]
+    fn test_save_reviews_appends_to_csv_history() {
+        let file = create_csv("Polski,English\nKot,Cat\n");
+        let conn_string = format!("file://{}", file.path().display());
+        let mut db =
+            Database::new(&conn_string, &CsvDialect::default()).expect("Failed to open database");
+
+        let phrase = db.get_phrases()[0].clone();
+        assert!(phrase.id > 0);
+        db.save_reviews(&[review(&phrase)]).unwrap();
+        db.save_reviews(&[review(&phrase)]).unwrap();
+
+        let history_path = file.path().with_extension("history.csv");
+        let history = std::fs::read_to_string(&history_path).unwrap();
+        let lines: Vec<&str> = history.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "reviewed_at,deck,original,translation,direction,attempts,recognized,hints"
+        );
+        let deck = file.path().file_stem().unwrap().to_string_lossy();
+        assert!(lines[1].ends_with(&format!(",{},Kot,Cat,reverse,2,true,1", deck)));
+        assert_eq!(db.get_reviews().unwrap().len(), 2);
+        std::fs::remove_file(&history_path).unwrap();
+    }
+
+    #[test]
+    fn test_old_csv_history_is_migrated() {
+        let file = create_csv("Polski,English\nKot,Cat\n");
+        let history_path = file.path().with_extension("history.csv");
+        std::fs::write(
+            &history_path,
+            "reviewed_at,original,translation,attempts,recognized\n\
+             2024-01-01T10:00:00+00:00,Kot,Cat,1,true\n",
+        )
+        .unwrap();
+
+        let conn_string = format!("file://{}", file.path().display());
+        let db = Database::new(&conn_string, &CsvDialect::default()).unwrap();
+
+        let history = std::fs::read_to_string(&history_path).unwrap();
+        assert_eq!(
+            history.lines().collect::<Vec<_>>(),
+            vec![
+                "reviewed_at,deck,original,translation,direction,attempts,recognized,hints",
+                "2024-01-01T10:00:00+00:00,,Kot,Cat,forward,1,true,0",
+            ]
+        );
+        assert_eq!(db.get_reviews().unwrap()[0].hints, 0);
+        std::fs::remove_file(&history_path).unwrap();
+    }
+
+    #[test]
+    fn test_remove_phrase_from_csv() {
+        let file = create_csv("Polski,English\nKot,Cat\nPies,Dog\nPtak,Bird");
+        let conn_string = format!("file://{}", file.path().display());
+        let mut db =
+            Database::new(&conn_string, &CsvDialect::default()).expect("Failed to open database");
+
+        let ids: Vec<PhraseId> = db.get_phrases().iter().map(|p| p.id).collect();
+        let ptak_id = ids[2];
+
+        // Ids stay the same after a removal, so scripts can remove several phrases in a row
+        assert!(db.remove_phrase(ids[0]).unwrap());
+        assert!(db.remove_phrase(ids[1]).unwrap());
+        assert!(!db.remove_phrase(ids[1]).unwrap());
+        assert!(!db.remove_phrase(0).unwrap());
+
+        let phrases: Vec<(PhraseId, &str)> = db
+            .get_phrases()
+            .iter()
+            .map(|p| (p.id, p.original.as_str()))
+            .collect();
+        assert_eq!(phrases, vec![(ptak_id, "Ptak")]);
+        let content = std::fs::read_to_string(file.path()).unwrap();
+        assert!(content.starts_with("Polski,English"));
+    }
+
+    fn headerless() -> CsvDialect {
+        CsvDialect {
+            has_headers: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_read_headerless_csv() {
+        let file = create_csv("Kot,Cat\nPies,Dog\nKot,Tomcat\n");
+        let conn_string = format!("file://{}", file.path().display());
+        let db = Database::new(&conn_string, &headerless()).expect("Failed to open database");
+
+        let originals: Vec<&str> = db
+            .get_phrases()
+            .iter()
+            .map(|p| p.original.as_str())
+            .collect();
+        assert_eq!(originals, vec!["Kot", "Pies", "Kot"]);
+        let ids: HashSet<PhraseId> = db.get_phrases().iter().map(|p| p.id).collect();
+        assert_eq!(ids.len(), 3);
+        assert_eq!(db.get_phrases()[0].source_language, None);
+    }
+
+    #[test]
+    fn test_remove_phrase_from_headerless_csv() {
+        let file = create_csv("Kot,Cat\nPies,Dog\nPtak,Bird\n");
+        let conn_string = format!("file://{}", file.path().display());
+        let mut db = Database::new(&conn_string, &headerless()).expect("Failed to open database");
+
+        let pies_id = db.get_phrases()[1].id;
+        assert!(db.remove_phrase(pies_id).unwrap());
+        assert!(!db.remove_phrase(pies_id).unwrap());
+
+        let content = std::fs::read_to_string(file.path()).unwrap();
+        assert_eq!(content, "Kot,Cat\nPtak,Bird\n");
+    }
+
+    #[test]
+    fn test_csv_directory_of_decks() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let decks = dir.path().join("decks");
+        std::fs::create_dir(&decks).unwrap();
+        std::fs::write(
+            decks.join("idioms.csv"),
+            "Polski,English\nKot,Cat\nPies,Dog\n",
+        )
+        .unwrap();
+        std::fs::write(decks.join("news.csv"), "Polski,English\nPtak,Bird\n").unwrap();
+        let conn_string = format!("file://{}", decks.display());
+        let mut db =
+            Database::new(&conn_string, &CsvDialect::default()).expect("Failed to open database");
+
+        assert_eq!(
+            db.get_decks(),
+            vec![("idioms".to_string(), 2), ("news".to_string(), 1)]
+        );
+        let news_id =
+            |db: &Database| db.get_filtered_phrases(Some("news"), &TagFilter::default())[0].id;
+        let ptak_id = news_id(&db);
+
+        // Adding a deck sorted before the others leaves ids of their phrases as they were
+        let mut phrase = Phrase::new("Ryba".to_string(), vec!["Fish".to_string()]);
+        phrase.deck = Some("business.en".to_string());
+        assert!(db.add_phrase(phrase).unwrap());
+        assert!(decks.join("business.en.csv").exists());
+        assert_eq!(news_id(&db), ptak_id);
+
+        let mut phrase = Phrase::new("Koń".to_string(), vec!["Horse".to_string()]);
+        phrase.deck = Some("../outside".to_string());
+        assert!(db.add_phrase(phrase).is_err());
+
+        let kot_id = db.get_phrases()[1].id;
+        assert!(db.remove_phrase(kot_id).unwrap());
+        let originals: Vec<&str> = db
+            .get_phrases()
+            .iter()
+            .map(|p| p.original.as_str())
+            .collect();
+        assert_eq!(originals, vec!["Ryba", "Pies", "Ptak"]);
+        assert_eq!(news_id(&db), ptak_id);
+    }
+
+    #[test]
+    fn test_csv_history_is_kept_per_deck() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let decks = dir.path().join("decks");
+        std::fs::create_dir(&decks).unwrap();
+        std::fs::write(decks.join("animals.csv"), "Polski,English\nZamek,Lock\n").unwrap();
+        std::fs::write(decks.join("travel.csv"), "Polski,English\nZamek,Castle\n").unwrap();
+        let conn_string = format!("file://{}", decks.display());
+        let mut db =
+            Database::new(&conn_string, &CsvDialect::default()).expect("Failed to open database");
+
+        let animals = db.get_filtered_phrases(Some("animals"), &TagFilter::default())[0].clone();
+        db.save_reviews(&[review(&animals)]).unwrap();
+
+        let reviews = db.get_reviews().unwrap();
+        assert_eq!(reviews.len(), 1);
+        assert_eq!(reviews[0].phrase, animals);
+        assert_eq!(reviews[0].phrase.translations, vec!["Lock"]);
+    }
+
+    #[test]
+    fn test_reload_if_changed() {
+        let file = create_csv("Polski,English\nKot,Cat\n");
+        let conn_string = format!("file://{}", file.path().display());
+        let mut db = Database::new(&conn_string, &CsvDialect::default()).unwrap();
+        assert!(!db.reload_if_changed().unwrap());
+        let modified_at = Database::phrases_modified_at(&conn_string).unwrap();
+        assert!(modified_at.is_some());
+
+        std::fs::write(file.path(), "Polski,English\nKot,Cat\nPies,Dog\n").unwrap();
+        // Make sure the change is noticed on file systems with a coarse timestamp resolution
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        file.as_file().set_modified(later).unwrap();
+
+        assert!(db.reload_if_changed().unwrap());
+        assert_eq!(db.get_phrases().len(), 2);
+        assert_ne!(
+            Database::phrases_modified_at(&conn_string).unwrap(),
+            modified_at
+        );
+        assert!(!db.reload_if_changed().unwrap());
+    }
+
+    #[test]
+    fn test_sqlite_import_and_reopen() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let conn_string = format!("sqlite://{}", dir.path().join("phrases.db").display());
+        let csv = create_csv("Polski,English\nKot,Cat\nPies,Dog|Hound\n");
+
+        let mut db =
+            Database::new(&conn_string, &CsvDialect::default()).expect("Failed to open database");
+        assert_eq!(db.import_csv(csv.path()).unwrap(), 2);
+        assert_eq!(db.import_csv(csv.path()).unwrap(), 0);
+        let phrase = db.get_phrases()[0].clone();
+        db.save_reviews(&[review(&phrase)]).unwrap();
+        drop(db);
+
+        let db =
+            Database::new(&conn_string, &CsvDialect::default()).expect("Failed to reopen database");
+        assert_eq!(db.get_phrases().len(), 2);
+        assert_eq!(db.get_phrases()[1].translations, vec!["Dog", "Hound"]);
+        assert_eq!(
+            db.get_phrases()[1].source_language.as_deref(),
+            Some("Polski")
+        );
+        assert_eq!(
+            db.get_phrases()[1].target_language.as_deref(),
+            Some("English")
+        );
+        let reviews = db.get_reviews().unwrap();
+        assert_eq!(reviews.len(), 1);
+        assert_eq!(reviews[0].phrase, phrase);
+        assert_eq!(reviews[0].direction, Direction::Reverse);
     }
 }
@@ -0,0 +1,403 @@
+use anyhow::Context;
+use log::{debug, trace};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+use super::phrase::{Phrase, Phrases, join_translations, split_translations};
+
+/// Separates fields of a note in Anki's collection database.
+const FIELD_SEPARATOR: char = '\x1f';
+
+/// Collection files found in `.apkg` packages, the preferred one first, along with whether
+/// they're compressed with zstd. Packages of recent Anki versions hold the compressed
+/// collection next to a legacy one containing only a note asking to upgrade.
+const COLLECTIONS: [(&str, bool); 3] = [
+    ("collection.anki21b", true),
+    ("collection.anki21", false),
+    ("collection.anki2", false),
+];
+
+/// Reads notes from an Anki export: an `.apkg`/`.colpkg` package or the "Notes in Plain Text"
+/// file, told apart by the extension.
+///
+/// The first field of a note becomes the phrase and the second one its translation, other
+/// fields are ignored. HTML formatting is dropped and the deck is named after the file.
+pub fn read_notes(path: &Path) -> anyhow::Result<Phrases> {
+    let is_package = path.extension().is_some_and(|extension| {
+        extension.eq_ignore_ascii_case("apkg") || extension.eq_ignore_ascii_case("colpkg")
+    });
+    let mut phrases = if is_package {
+        read_package(path)?
+    } else {
+        read_text(path)?
+    };
+
+    let deck = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned());
+    for phrase in &mut phrases {
+        phrase.deck = deck.clone();
+    }
+    debug!("Read {} notes from {:?}", phrases.len(), path);
+    Ok(phrases)
+}
+
+/// Reads the "Notes in Plain Text" export.
+///
+/// The `#separator`, `#html`, `#tags column` and the `#guid`, `#notetype` and `#deck column`
+/// header lines are honoured. Files without a header, written by Anki before 2.1.55, are
+/// tab-separated and hold the tags in the last of three or more columns.
+fn read_text(path: &Path) -> anyhow::Result<Phrases> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to open {:?}", path))?;
+
+    let mut directives = HashMap::new();
+    let mut body = content.as_str();
+    while let Some(line) = body.strip_prefix('#') {
+        let (line, rest) = line.split_once('\n').unwrap_or((line, ""));
+        if let Some((key, value)) = line.trim_end().split_once(':') {
+            directives.insert(key.to_string(), value.to_string());
+        }
+        body = rest;
+    }
+    trace!("Anki export header: {:?}", directives);
+
+    let delimiter = match directives.get("separator").map(String::as_str) {
+        None | Some("tab") | Some("Tab") => b'\t',
+        Some("comma") | Some("Comma") => b',',
+        Some("semicolon") | Some("Semicolon") => b';',
+        Some("pipe") | Some("Pipe") => b'|',
+        Some("space") | Some("Space") => b' ',
+        Some("colon") | Some("Colon") => b':',
+        Some(other) if other.len() == 1 => other.as_bytes()[0],
+        Some(other) => anyhow::bail!("Unsupported separator '{}' in {:?}", other, path),
+    };
+    let is_html = directives.get("html").is_none_or(|html| html != "false");
+    let column = |key: &str| -> anyhow::Result<Option<usize>> {
+        directives
+            .get(key)
+            .map(|value| {
+                value
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|column| column.checked_sub(1))
+                    .with_context(|| format!("Invalid '{}' '{}' in {:?}", key, value, path))
+            })
+            .transpose()
+    };
+    let tags_column = column("tags column")?;
+    let mut skipped_columns = Vec::new();
+    for key in ["guid column", "notetype column", "deck column"] {
+        skipped_columns.extend(column(key)?);
+    }
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(body.as_bytes());
+    let mut phrases = Vec::new();
+    for result in reader.records() {
+        let record = result.with_context(|| format!("Failed to read {:?}", path))?;
+        let tags_column = match tags_column {
+            Some(column) => Some(column),
+            None if directives.is_empty() && record.len() > 2 => Some(record.len() - 1),
+            None => None,
+        };
+        let fields: Vec<&str> = record
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| Some(*idx) != tags_column && !skipped_columns.contains(idx))
+            .map(|(_, field)| field)
+            .collect();
+        let tags = tags_column
+            .and_then(|column| record.get(column))
+            .map(|tags| tags.split_whitespace().map(String::from).collect());
+
+        match note_to_phrase(&fields, tags.unwrap_or_default(), is_html) {
+            Some(phrase) => phrases.push(phrase),
+            None => trace!("Note skipped: {:?}", record),
+        }
+    }
+    Ok(phrases)
+}
+
+/// Reads notes from the collection database packed in an `.apkg` or `.colpkg` file.
+fn read_package(path: &Path) -> anyhow::Result<Phrases> {
+    let file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("{:?} is not a valid Anki package", path))?;
+
+    let (name, is_compressed) = COLLECTIONS
+        .into_iter()
+        .find(|(name, _)| archive.index_for_name(name).is_some())
+        .with_context(|| format!("No collection found in Anki package {:?}", path))?;
+    let mut collection = Vec::new();
+    archive
+        .by_name(name)?
+        .read_to_end(&mut collection)
+        .with_context(|| format!("Failed to unpack {} from {:?}", name, path))?;
+    if is_compressed {
+        collection = zstd::decode_all(collection.as_slice())
+            .with_context(|| format!("Failed to decompress {} from {:?}", name, path))?;
+    }
+    trace!(
+        "Unpacked {} ({} bytes) from {:?}",
+        name,
+        collection.len(),
+        path
+    );
+
+    // SQLite needs a file to open, it's removed once the notes are read
+    let mut collection_file = tempfile::Builder::new()
+        .prefix("phrasey-")
+        .suffix(&format!("-{}", name))
+        .tempfile()
+        .context("Failed to create a file for the unpacked Anki collection")?;
+    collection_file
+        .write_all(&collection)
+        .context("Failed to write the unpacked Anki collection")?;
+    read_collection(collection_file.path())
+}
+
+fn read_collection(path: &Path) -> anyhow::Result<Phrases> {
+    let conn =
+        rusqlite::Connection::open_with_flags(path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .context("Failed to open the Anki collection")?;
+    let mut statement = conn
+        .prepare("SELECT flds, tags FROM notes ORDER BY id")
+        .context("Failed to read notes of the Anki collection")?;
+    let notes = statement
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut phrases = Vec::new();
+    for (fields, tags) in notes {
+        let fields: Vec<&str> = fields.split(FIELD_SEPARATOR).collect();
+        let tags = tags.split_whitespace().map(String::from).collect();
+        match note_to_phrase(&fields, tags, true) {
+            Some(phrase) => phrases.push(phrase),
+            None => trace!("Note skipped: {:?}", fields),
+        }
+    }
+    Ok(phrases)
+}
+
+/// Maps the front and back fields of a note onto a phrase, `None` if either is empty.
+fn note_to_phrase(fields: &[&str], tags: Vec<String>, is_html: bool) -> Option<Phrase> {
+    let field = |idx: usize| {
+        let field = fields.get(idx).copied().unwrap_or_default();
+        if is_html {
+            strip_html(field)
+        } else {
+            field.trim().to_string()
+        }
+    };
+    let original = field(0);
+    let translations = split_translations(&field(1));
+    if original.is_empty() || translations.is_empty() {
+        return None;
+    }
+
+    let mut phrase = Phrase::new(original, translations);
+    phrase.tags = tags;
+    Some(phrase)
+}
+
+/// Writes phrases as an Anki "Notes in Plain Text" file with the phrase on the front, the
+/// translations on the back and the tags in the third column.
+///
+/// Anki tags can't contain spaces, so they're replaced with underscores.
+pub fn write_notes(path: &Path, phrases: &Phrases) -> anyhow::Result<()> {
+    let mut file = File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
+    file.write_all(b"#separator:tab\n#html:false\n#tags column:3\n")?;
+
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(b'\t')
+        .has_headers(false)
+        .from_writer(file);
+    for phrase in phrases {
+        let tags: Vec<String> = phrase
+            .tags
+            .iter()
+            .map(|tag| tag.replace(' ', "_"))
+            .collect();
+        writer.write_record([
+            &phrase.original,
+            &join_translations(&phrase.translations),
+            &tags.join(" "),
+        ])?;
+    }
+    writer.flush()?;
+
+    trace!("Written {} notes to {:?}", phrases.len(), path);
+    Ok(())
+}
+
+/// Turns an HTML field into plain text: drops tags and sound references, decodes common
+/// entities and collapses whitespace, treating line breaks and blocks as spaces.
+fn strip_html(field: &str) -> String {
+    let mut text = String::with_capacity(field.len());
+    let mut rest = field;
+    while let Some(start) = rest.find(['<', '[']) {
+        text.push_str(&rest[..start]);
+        let closing = if rest[start..].starts_with('<') {
+            '>'
+        } else if rest[start..].starts_with("[sound:") {
+            ']'
+        } else {
+            text.push('[');
+            rest = &rest[start + 1..];
+            continue;
+        };
+        let Some(end) = rest[start..].find(closing) else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end]
+            .trim_start_matches('/')
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        if ["br", "div", "p", "li", "tr", "td"].contains(&tag.as_str()) {
+            text.push(' ');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_text_export_with_header() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("idioms.txt");
+        std::fs::write(
+            &path,
+            "#separator:tab\n#html:true\n#notetype column:1\n#tags column:4\n\
+             Basic\tKot<br>domowy\t<b>Cat</b>&nbsp;[sound:cat.mp3]\tanimal pets\n\
+             Basic\t\"Pies\tbury\"\tDog\t\n\
+             Basic\tEmpty\t\t\n",
+        )
+        .unwrap();
+
+        let phrases = read_notes(&path).unwrap();
+
+        assert_eq!(phrases.len(), 2);
+        assert_eq!(phrases[0].original, "Kot domowy");
+        assert_eq!(phrases[0].translations, vec!["Cat"]);
+        assert_eq!(phrases[0].tags, vec!["animal", "pets"]);
+        assert_eq!(phrases[0].deck.as_deref(), Some("idioms"));
+        assert_eq!(phrases[1].original, "Pies bury");
+        assert!(phrases[1].tags.is_empty());
+    }
+
+    /// Returns an Anki collection database holding notes with the given fields and tags.
+    fn create_collection(dir: &Path, notes: &[(&str, &str)]) -> Vec<u8> {
+        let path = dir.join("collection.db");
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        conn.execute(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, flds TEXT NOT NULL, tags TEXT NOT NULL)",
+            [],
+        )
+        .unwrap();
+        for (fields, tags) in notes {
+            conn.execute(
+                "INSERT INTO notes (flds, tags) VALUES (?1, ?2)",
+                [fields, tags],
+            )
+            .unwrap();
+        }
+        drop(conn);
+        let collection = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        collection
+    }
+
+    fn create_package(path: &Path, files: &[(&str, &[u8])]) {
+        let mut archive = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in files {
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Stored);
+            archive.start_file(*name, options).unwrap();
+            archive.write_all(content).unwrap();
+        }
+        archive.finish().unwrap();
+    }
+
+    #[test]
+    fn test_read_packages() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let legacy = create_collection(
+            dir.path(),
+            &[("Please update to the latest Anki version\x1f", "")],
+        );
+        let notes = create_collection(
+            dir.path(),
+            &[
+                ("Kot\x1f<b>Cat</b>|Kitty", " animal pets "),
+                ("Pusta\x1f", ""),
+                ("Pies\x1fDog", ""),
+            ],
+        );
+
+        let apkg = dir.path().join("idioms.APKG");
+        create_package(&apkg, &[("collection.anki2", &notes)]);
+        let colpkg = dir.path().join("backup.colpkg");
+        let compressed = zstd::encode_all(notes.as_slice(), 0).unwrap();
+        create_package(
+            &colpkg,
+            &[
+                ("collection.anki2", &legacy),
+                ("collection.anki21b", &compressed),
+            ],
+        );
+
+        let phrases = read_notes(&apkg).unwrap();
+        assert_eq!(phrases.len(), 2);
+        assert_eq!(phrases[0].original, "Kot");
+        assert_eq!(phrases[0].translations, vec!["Cat", "Kitty"]);
+        assert_eq!(phrases[0].tags, vec!["animal", "pets"]);
+        assert_eq!(phrases[0].deck.as_deref(), Some("idioms"));
+        assert_eq!(phrases[1].original, "Pies");
+
+        let phrases = read_notes(&colpkg).unwrap();
+        assert_eq!(phrases.len(), 2);
+        assert_eq!(phrases[1].translations, vec!["Dog"]);
+        assert_eq!(phrases[1].deck.as_deref(), Some("backup"));
+    }
+
+    #[test]
+    fn test_write_notes_can_be_read_back() {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let path = dir.path().join("export.txt");
+        let mut phrase = Phrase::new("Kot".to_string(), vec!["Cat".into(), "Kitty".into()]);
+        phrase.tags = vec!["small animals".to_string(), "easy".to_string()];
+
+        write_notes(&path, &vec![phrase]).unwrap();
+        let phrases = read_notes(&path).unwrap();
+
+        assert_eq!(phrases[0].original, "Kot");
+        assert_eq!(phrases[0].translations, vec!["Cat", "Kitty"]);
+        assert_eq!(phrases[0].tags, vec!["small_animals", "easy"]);
+    }
+}
@@ -0,0 +1,281 @@
+use anyhow::Context;
+use encoding_rs::Encoding;
+use log::warn;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::path::Path;
+
+use super::phrase::{Phrase, join_tags, join_translations, split_tags, split_translations};
+
+/// Layout of CSV phrase files, configured in the `[csv]` table of the config file.
+///
+/// Columns are numbered from 1. The history file kept next to the phrases is always written
+/// by the app itself and doesn't follow the dialect.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CsvDialect {
+    pub delimiter: char,
+    pub quote: char,
+    /// Whether the first row is a header, naming the languages, rather than a phrase.
+    pub has_headers: bool,
+    /// Label of the file encoding, e.g. "utf-8" or "windows-1250".
+    pub encoding: String,
+    pub original_column: usize,
+    /// Column of the accepted translations, separated with `|`.
+    pub translation_column: usize,
+    /// Column of the tags, separated with `;`.
+    #[serde(with = "optional_column")]
+    pub tags_column: Option<usize>,
+    #[serde(with = "optional_column")]
+    pub notes_column: Option<usize>,
+}
+
+/// Writes a column that isn't set as 0, so a cleared column with a default stays cleared.
+mod optional_column {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        column: &Option<usize>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(column.unwrap_or(0) as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<usize>, D::Error> {
+        Ok(Some(usize::deserialize(deserializer)?).filter(|column| *column != 0))
+    }
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        CsvDialect {
+            delimiter: ',',
+            quote: '"',
+            has_headers: true,
+            encoding: "utf-8".to_string(),
+            original_column: 1,
+            translation_column: 2,
+            tags_column: Some(3),
+            notes_column: None,
+        }
+    }
+}
+
+impl CsvDialect {
+    /// Checks the delimiter and the quote are distinct characters usable by the CSV parser.
+    pub fn validate_characters(&self) -> anyhow::Result<()> {
+        for (name, c) in [("delimiter", self.delimiter), ("quote", self.quote)] {
+            if !c.is_ascii() || c == '\n' || c == '\r' {
+                anyhow::bail!("CSV {} must be a single ASCII character.", name);
+            }
+        }
+        if self.delimiter == self.quote {
+            anyhow::bail!("CSV delimiter and quote must be different characters.");
+        }
+        Ok(())
+    }
+
+    pub fn validate_encoding(&self) -> anyhow::Result<()> {
+        self.encoding().map(|_| ())
+    }
+
+    /// Checks the columns are numbered from 1 and different from each other.
+    pub fn validate_columns(&self) -> anyhow::Result<()> {
+        let columns = self.columns();
+        if columns.contains(&0) {
+            anyhow::bail!("CSV columns are numbered from 1.");
+        }
+        if (1..columns.len()).any(|idx| columns[idx..].contains(&columns[idx - 1])) {
+            anyhow::bail!("CSV columns must be different from each other.");
+        }
+        Ok(())
+    }
+
+    fn columns(&self) -> Vec<usize> {
+        [self.original_column, self.translation_column]
+            .into_iter()
+            .chain(self.tags_column)
+            .chain(self.notes_column)
+            .collect()
+    }
+
+    fn encoding(&self) -> anyhow::Result<&'static Encoding> {
+        let encoding = Encoding::for_label(self.encoding.trim().as_bytes())
+            .with_context(|| format!("Unknown CSV encoding '{}'.", self.encoding))?;
+        if !encoding.is_ascii_compatible() {
+            anyhow::bail!("CSV encoding '{}' is not supported.", self.encoding);
+        }
+        Ok(encoding)
+    }
+
+    /// Reads the file and decodes it into text, dropping a byte order mark.
+    pub fn read_file(&self, path: &Path) -> anyhow::Result<String> {
+        let bytes = std::fs::read(path).with_context(|| format!("Failed to open {:?}", path))?;
+        let (text, had_errors) = self.encoding()?.decode_with_bom_removal(&bytes);
+        if had_errors {
+            warn!(
+                "{:?} is not valid {}, invalid characters were replaced",
+                path, self.encoding
+            );
+        }
+        Ok(text.into_owned())
+    }
+
+    /// Encodes text written to a phrase file.
+    pub fn encode(&self, text: &str) -> anyhow::Result<Vec<u8>> {
+        let (bytes, _, had_unmappable) = self.encoding()?.encode(text);
+        if had_unmappable {
+            anyhow::bail!("Some characters can't be written in {}", self.encoding);
+        }
+        Ok(bytes.into_owned())
+    }
+
+    /// Returns a reader of all rows, the header included.
+    pub fn reader<'a>(&self, text: &'a str) -> csv::Reader<&'a [u8]> {
+        csv::ReaderBuilder::new()
+            .delimiter(self.delimiter as u8)
+            .quote(self.quote as u8)
+            .has_headers(false)
+            .flexible(true)
+            .from_reader(text.as_bytes())
+    }
+
+    /// Returns a writer producing rows of the dialect, without adding a header.
+    pub fn writer(&self) -> csv::Writer<Vec<u8>> {
+        csv::WriterBuilder::new()
+            .delimiter(self.delimiter as u8)
+            .quote(self.quote as u8)
+            .has_headers(false)
+            .flexible(true)
+            .from_writer(Vec::new())
+    }
+
+    /// Returns the source and target languages named by the header row.
+    pub fn languages(&self, header: &csv::StringRecord) -> (Option<String>, Option<String>) {
+        let language = |column: usize| header.get(column - 1).and_then(header_language);
+        (
+            language(self.original_column),
+            language(self.translation_column),
+        )
+    }
+
+    /// Maps a row onto a phrase, or returns why the row can't be used.
+    pub fn phrase(&self, record: &csv::StringRecord) -> Result<Phrase, String> {
+        let field = |column: usize| record.get(column - 1).map(str::trim);
+        let original = field(self.original_column)
+            .filter(|original| !original.is_empty())
+            .ok_or_else(|| format!("no phrase in column {}", self.original_column))?;
+        let translations = field(self.translation_column)
+            .map(split_translations)
+            .filter(|translations| !translations.is_empty())
+            .ok_or_else(|| format!("no translation in column {}", self.translation_column))?;
+
+        let mut phrase = Phrase::new(original.to_string(), translations);
+        phrase.tags = self
+            .tags_column
+            .and_then(field)
+            .map(split_tags)
+            .unwrap_or_default();
+        phrase.notes = self
+            .notes_column
+            .and_then(field)
+            .filter(|notes| !notes.is_empty())
+            .map(String::from);
+        Ok(phrase)
+    }
+
+    /// Returns the row of the phrase, leaving out trailing empty optional columns.
+    pub fn record(&self, phrase: &Phrase) -> Vec<String> {
+        let mut fields = [
+            (Some(self.original_column), phrase.original.clone()),
+            (
+                Some(self.translation_column),
+                join_translations(&phrase.translations),
+            ),
+            (self.tags_column, join_tags(&phrase.tags)),
+            (self.notes_column, phrase.notes.clone().unwrap_or_default()),
+        ];
+        for field in &mut fields[2..] {
+            if field.1.is_empty() {
+                field.0 = None;
+            }
+        }
+        self.place(&fields)
+    }
+
+    /// Returns the header row naming the languages, or generic names for unknown ones.
+    pub fn header(&self, source: Option<&str>, target: Option<&str>) -> Vec<String> {
+        self.place(&[
+            (
+                Some(self.original_column),
+                source.unwrap_or("Original").to_string(),
+            ),
+            (
+                Some(self.translation_column),
+                target.unwrap_or("Translation").to_string(),
+            ),
+            (self.tags_column, "Tags".to_string()),
+            (self.notes_column, "Notes".to_string()),
+        ])
+    }
+
+    fn place(&self, fields: &[(Option<usize>, String)]) -> Vec<String> {
+        let width = fields
+            .iter()
+            .filter_map(|(column, _)| *column)
+            .max()
+            .unwrap_or_default();
+        let mut record = vec![String::new(); width];
+        for (column, value) in fields {
+            if let Some(column) = column {
+                record[column - 1] = value.clone();
+            }
+        }
+        record
+    }
+}
+
+/// Returns the language named by a header cell, `None` for generic column names.
+fn header_language(header: &str) -> Option<String> {
+    let header = header.trim();
+    let is_generic = ["", "original", "translation"].contains(&header.to_lowercase().as_str());
+    (!is_generic).then(|| header.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_columns_round_trip() {
+        let dialect = CsvDialect {
+            delimiter: ';',
+            original_column: 2,
+            translation_column: 1,
+            tags_column: None,
+            notes_column: Some(4),
+            ..Default::default()
+        };
+        let mut phrase = Phrase::new("Kot".to_string(), vec!["Cat".to_string()]);
+        phrase.notes = Some("Pet".to_string());
+
+        let record = dialect.record(&phrase);
+        assert_eq!(record, vec!["Cat", "Kot", "", "Pet"]);
+        let read = dialect.phrase(&csv::StringRecord::from(record)).unwrap();
+        assert_eq!(read, phrase);
+        assert!(
+            dialect
+                .phrase(&csv::StringRecord::from(vec!["Cat"]))
+                .is_err()
+        );
+        assert!(
+            CsvDialect {
+                tags_column: Some(1),
+                ..Default::default()
+            }
+            .validate_columns()
+            .is_err()
+        );
+    }
+}
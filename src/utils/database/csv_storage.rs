@@ -0,0 +1,487 @@
+use anyhow::Context;
+use chrono::DateTime;
+use log::{debug, trace, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::csv_dialect::CsvDialect;
+use super::phrase::{Phrase, PhraseId, Phrases, join_translations};
+use super::{Review, Storage};
+
+use crate::types::Direction;
+
+#[derive(Debug, Deserialize, Serialize)]
+struct ReviewRow {
+    reviewed_at: String,
+    /// Deck of the phrase, missing in rows written before phrases were told apart by deck.
+    #[serde(default)]
+    deck: Option<String>,
+    original: String,
+    translation: String,
+    #[serde(default)]
+    direction: Direction,
+    attempts: usize,
+    recognized: bool,
+    #[serde(default)]
+    hints: usize,
+}
+
+/// Number of ids phrases of CSV files are spread over, keeping them short enough to type.
+const PHRASE_IDS: u64 = 999_999_999;
+
+/// Flat-file storage: phrases are read from a CSV file and the review history is appended
+/// to a second CSV file next to it, e.g. `db.csv` -> `db.history.csv`.
+///
+/// The path may also point to a directory, in which case every CSV file in it is a deck named
+/// after the file, e.g. `decks/idioms.csv` holds the `idioms` deck, and the history is kept
+/// next to the directory in `decks.history.csv`.
+///
+/// Ids of phrases are derived from their deck and original text, see `phrase_id`, so adding,
+/// removing or reordering phrases doesn't change ids of the other ones.
+///
+/// Phrase files are read and written in the configured `CsvDialect`.
+pub struct CsvStorage {
+    path: PathBuf,
+    history_path: PathBuf,
+    dialect: CsvDialect,
+}
+
+impl CsvStorage {
+    pub fn open(path: &str, dialect: &CsvDialect) -> anyhow::Result<Self> {
+        let path = PathBuf::from(path);
+        if !path.exists() {
+            anyhow::bail!("Phrase file {:?} does not exist", path);
+        }
+        let history_path = path.with_extension("history.csv");
+        let storage = CsvStorage {
+            path,
+            history_path,
+            dialect: dialect.clone(),
+        };
+        storage.migrate_history()?;
+
+        debug!(
+            "CSV storage opened, history kept in {:?}",
+            storage.history_path
+        );
+        Ok(storage)
+    }
+
+    /// Rewrites history files created before reviews were tracked per direction and deck or
+    /// counted hints, so new rows match the header. Old reviews are assumed to be forward
+    /// ones taken without hints, their deck is left empty.
+    fn migrate_history(&self) -> anyhow::Result<()> {
+        if !self.history_path.exists() {
+            return Ok(());
+        }
+
+        let mut reader = csv::Reader::from_path(&self.history_path)
+            .context("Failed to open review history file")?;
+        if reader.headers()?.iter().any(|header| header == "deck") {
+            return Ok(());
+        }
+        let rows = reader
+            .deserialize()
+            .collect::<Result<Vec<ReviewRow>, _>>()
+            .context("Failed to read review history")?;
+        drop(reader);
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for row in &rows {
+            writer.serialize(row)?;
+        }
+        replace_file(&self.history_path, &writer.into_inner()?)?;
+
+        debug!(
+            "Migrated {} reviews in {:?} to the current history format",
+            rows.len(),
+            self.history_path
+        );
+        Ok(())
+    }
+
+    /// Returns the file new phrases of the deck are appended to.
+    fn deck_file(&self, deck: Option<&str>) -> anyhow::Result<PathBuf> {
+        if !self.path.is_dir() {
+            return Ok(self.path.clone());
+        }
+        let deck = deck.context("Phrases added to a directory of decks need a deck name")?;
+        if deck.is_empty() || deck.contains(['/', '\\']) || deck.contains("..") {
+            anyhow::bail!("Invalid deck name '{}'", deck);
+        }
+        Ok(self.path.join(format!("{}.csv", deck)))
+    }
+}
+
+impl Storage for CsvStorage {
+    fn load_phrases(&self) -> anyhow::Result<Phrases> {
+        let mut phrases = Vec::new();
+        let mut ids = HashSet::new();
+        for file in deck_files(&self.path)? {
+            let deck_phrases = read_deck(&file, &self.dialect, &mut ids)?;
+            phrases.extend(deck_phrases.into_iter().map(|(_, phrase)| phrase));
+        }
+        Ok(phrases)
+    }
+
+    fn insert_phrases(&mut self, phrases: &Phrases) -> anyhow::Result<usize> {
+        let mut existing: HashSet<_> = self
+            .load_phrases()?
+            .into_iter()
+            .map(|phrase| (phrase.original, phrase.translations))
+            .collect();
+        let mut inserted = 0;
+        for phrase in phrases {
+            if !existing.insert((phrase.original.clone(), phrase.translations.clone())) {
+                trace!("Phrase already stored: {:?}", phrase);
+                continue;
+            }
+            append_phrase(
+                &self.deck_file(phrase.deck.as_deref())?,
+                phrase,
+                &self.dialect,
+            )?;
+            inserted += 1;
+            trace!("Phrase appended: {:?}", phrase);
+        }
+
+        debug!("Appended {} new phrases to {:?}", inserted, self.path);
+        Ok(inserted)
+    }
+
+    fn remove_phrase(&mut self, id: PhraseId) -> anyhow::Result<bool> {
+        let mut ids = HashSet::new();
+        for file in deck_files(&self.path)? {
+            let Some(row) = read_deck(&file, &self.dialect, &mut ids)?
+                .into_iter()
+                .find_map(|(row, phrase)| (phrase.id == id).then_some(row))
+            else {
+                continue;
+            };
+
+            // The header row is kept, rows are counted from the first data row
+            let text = self.dialect.read_file(&file)?;
+            let records = self
+                .dialect
+                .reader(&text)
+                .records()
+                .collect::<Result<Vec<_>, _>>()
+                .with_context(|| format!("Failed to read {:?}", file))?;
+            let row = row + usize::from(self.dialect.has_headers);
+
+            let mut writer = self.dialect.writer();
+            for (idx, record) in records.iter().enumerate() {
+                if idx != row {
+                    writer.write_record(record)?;
+                }
+            }
+            let text = String::from_utf8(writer.into_inner()?)?;
+            replace_file(&file, &self.dialect.encode(&text)?)?;
+
+            debug!("Removed phrase {} from {:?}", id, file);
+            return Ok(true);
+        }
+
+        trace!("Phrase {} not found in {:?}", id, self.path);
+        Ok(false)
+    }
+
+    fn load_reviews(&self) -> anyhow::Result<Vec<Review>> {
+        if !self.history_path.exists() {
+            trace!("No review history at {:?}", self.history_path);
+            return Ok(Vec::new());
+        }
+
+        // History refers to phrases by their deck and original text, so it survives
+        // reordering the files. Rows without a deck refer to the first phrase with the text.
+        let phrases = self.load_phrases()?;
+        let mut by_deck = HashMap::new();
+        let mut by_original = HashMap::new();
+        for phrase in &phrases {
+            by_deck
+                .entry((phrase.deck.as_deref(), phrase.original.as_str()))
+                .or_insert(phrase);
+            by_original
+                .entry(phrase.original.as_str())
+                .or_insert(phrase);
+        }
+
+        let mut reviews = Vec::new();
+        let mut reader = csv::Reader::from_path(&self.history_path)
+            .context("Failed to open review history file")?;
+        for result in reader.deserialize() {
+            let row: ReviewRow = result.context("Failed to read review history")?;
+            let phrase = match row.deck.as_deref() {
+                Some(deck) => by_deck.get(&(Some(deck), row.original.as_str())),
+                None => by_original.get(row.original.as_str()),
+            };
+            let Some(phrase) = phrase else {
+                trace!("Phrase of review {:?} not found, skipping", row);
+                continue;
+            };
+            let reviewed_at = DateTime::parse_from_rfc3339(&row.reviewed_at)
+                .context("Failed to parse review timestamp")?;
+            reviews.push(Review {
+                phrase: (*phrase).clone(),
+                direction: row.direction,
+                attempts: row.attempts,
+                recognized: row.recognized,
+                hints: row.hints,
+                reviewed_at: reviewed_at.into(),
+            });
+        }
+
+        trace!(
+            "Loaded {} reviews from {:?}",
+            reviews.len(),
+            self.history_path
+        );
+        Ok(reviews)
+    }
+
+    fn phrases_modified_at(&self) -> anyhow::Result<Option<SystemTime>> {
+        phrases_modified_at(&self.path)
+    }
+
+    fn save_reviews(&mut self, reviews: &[Review]) -> anyhow::Result<()> {
+        let is_new = !self.history_path.exists();
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.history_path)
+            .context("Failed to open review history file")?;
+        let mut writer = csv::WriterBuilder::new()
+            .has_headers(is_new)
+            .from_writer(file);
+
+        for review in reviews {
+            writer.serialize(ReviewRow {
+                reviewed_at: review.reviewed_at.to_rfc3339(),
+                deck: review.phrase.deck.clone(),
+                original: review.phrase.original.clone(),
+                translation: join_translations(&review.phrase.translations),
+                direction: review.direction,
+                attempts: review.attempts,
+                recognized: review.recognized,
+                hints: review.hints,
+            })?;
+            trace!("Review saved: {:?}", review);
+        }
+        writer.flush()?;
+
+        debug!("Saved {} reviews to {:?}", reviews.len(), self.history_path);
+        Ok(())
+    }
+}
+
+/// Returns the phrase files, sorted by name when the path is a directory of decks.
+fn deck_files(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(path).context("Failed to list deck directory")? {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|extension| extension == "csv") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Returns the latest modification of the phrase files, or of the deck directory itself
+/// as adding or removing a deck doesn't touch the other files.
+pub fn phrases_modified_at(path: &Path) -> anyhow::Result<Option<SystemTime>> {
+    let mut paths = deck_files(path)?;
+    if path.is_dir() {
+        paths.push(path.to_path_buf());
+    }
+    let mut modified_at = None;
+    for path in paths {
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| format!("Failed to check modification time of {:?}", path))?;
+        modified_at = modified_at.max(Some(modified));
+    }
+    Ok(modified_at)
+}
+
+/// Reads phrases from a CSV file in the given dialect.
+///
+/// The header names the source and target languages of the deck, e.g. `Polski,English`,
+/// unless it's the generic `Original,Translation`. Translations may hold several accepted
+/// ones separated with `|` and tags are separated with `;`, e.g. `idiom;news`. The deck is
+/// named after the file and phrase ids are derived from it, see `phrase_id`. Rows that
+/// can't be read as phrases are skipped with a warning.
+pub fn read_phrases<P: AsRef<Path>>(path: P, dialect: &CsvDialect) -> anyhow::Result<Phrases> {
+    let phrases = read_deck(path.as_ref(), dialect, &mut HashSet::new())?;
+    Ok(phrases.into_iter().map(|(_, phrase)| phrase).collect())
+}
+
+/// Reads phrases of a single file, each with the 0-based number of its data row, counting
+/// skipped rows too. Phrases get ids that aren't in `ids` yet, which are added to it.
+fn read_deck(
+    path: &Path,
+    dialect: &CsvDialect,
+    ids: &mut HashSet<PhraseId>,
+) -> anyhow::Result<Vec<(usize, Phrase)>> {
+    let text = dialect.read_file(path)?;
+    let mut reader = dialect.reader(&text);
+    let mut records = reader.records();
+    let deck = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned());
+    let header = if dialect.has_headers {
+        records.next().transpose()
+    } else {
+        Ok(None)
+    };
+    let (source_language, target_language) =
+        match header.with_context(|| format!("Failed to read {:?}", path))? {
+            Some(header) => dialect.languages(&header),
+            None => (None, None),
+        };
+    trace!(
+        "CSV reader initialized for file: {:?}, languages: {:?} -> {:?}",
+        path, source_language, target_language
+    );
+
+    let mut phrases = Vec::new();
+    for (row, result) in records.enumerate() {
+        let record = result.with_context(|| format!("Failed to read {:?}", path))?;
+        match dialect.phrase(&record) {
+            Ok(mut phrase) => {
+                phrase.id = phrase_id(deck.as_deref(), &phrase.original, ids);
+                phrase.deck = deck.clone();
+                phrase.source_language = source_language.clone();
+                phrase.target_language = target_language.clone();
+                phrases.push((row, phrase));
+                trace!("Row added: {:?}", record);
+            }
+            Err(reason) => warn!(
+                "Skipped row on line {} of {:?}: {}",
+                record.position().map_or(0, |position| position.line()),
+                path,
+                reason
+            ),
+        }
+    }
+
+    trace!("Total records loaded from CSV: {}", phrases.len());
+    Ok(phrases)
+}
+
+/// Returns an id for the phrase derived from a hash of its deck and original text, so it
+/// stays the same when other phrases are added, removed or reordered. If the id is already
+/// in `ids`, e.g. for the same original text repeated in a deck, the next free one is taken.
+fn phrase_id(deck: Option<&str>, original: &str, ids: &mut HashSet<PhraseId>) -> PhraseId {
+    let bytes = deck
+        .unwrap_or_default()
+        .bytes()
+        .chain([0])
+        .chain(original.bytes());
+    // FNV-1a, unlike `DefaultHasher` it doesn't change between Rust releases
+    let hash = bytes.fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    });
+    let mut id = (hash % PHRASE_IDS) as PhraseId + 1;
+    while !ids.insert(id) {
+        id = id % PHRASE_IDS as PhraseId + 1;
+    }
+    id
+}
+
+/// Appends the phrase to a CSV file, creating the file with a header row if needed.
+fn append_phrase(path: &Path, phrase: &Phrase, dialect: &CsvDialect) -> anyhow::Result<()> {
+    let is_new = !path.exists();
+    let mut file = OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {:?}", path))?;
+
+    // The file may not end with a newline, so make sure new rows start on their own line
+    if file.metadata()?.len() > 0 {
+        let mut last_byte = [0u8; 1];
+        file.seek(SeekFrom::End(-1))?;
+        file.read_exact(&mut last_byte)?;
+        if last_byte[0] != b'\n' {
+            file.write_all(b"\n")?;
+        }
+    }
+
+    let mut writer = dialect.writer();
+    if is_new && dialect.has_headers {
+        writer.write_record(dialect.header(
+            phrase.source_language.as_deref(),
+            phrase.target_language.as_deref(),
+        ))?;
+    }
+    writer.write_record(dialect.record(phrase))?;
+    let text = String::from_utf8(writer.into_inner()?)?;
+    file.write_all(&dialect.encode(&text)?)?;
+    Ok(())
+}
+
+/// Replaces the content of the file through a temporary file next to it, so an interrupted
+/// write leaves either the old or the new version behind.
+fn replace_file(path: &Path, content: &[u8]) -> anyhow::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    let mut file =
+        File::create(&tmp_path).with_context(|| format!("Failed to create {:?}", tmp_path))?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    std::fs::rename(&tmp_path, path).with_context(|| format!("Failed to replace {:?}", path))?;
+    Ok(())
+}
+
+/// Writes phrases to a CSV file in the given dialect, readable by `read_phrases`.
+///
+/// The header names the languages if all phrases share the same language pair.
+pub fn write_phrases<P: AsRef<Path>>(
+    path: P,
+    phrases: &Phrases,
+    dialect: &CsvDialect,
+) -> anyhow::Result<()> {
+    let path = path.as_ref();
+    let mut writer = dialect.writer();
+    if dialect.has_headers {
+        let (source, target) = shared_languages(phrases);
+        writer.write_record(dialect.header(source, target))?;
+    }
+    for phrase in phrases {
+        writer.write_record(dialect.record(phrase))?;
+    }
+    let text = String::from_utf8(writer.into_inner()?)?;
+    std::fs::write(path, dialect.encode(&text)?)
+        .with_context(|| format!("Failed to create {:?}", path))?;
+
+    trace!("Written {} phrases to {:?}", phrases.len(), path);
+    Ok(())
+}
+
+/// Returns the language pair shared by all phrases, if there's one.
+fn shared_languages(phrases: &Phrases) -> (Option<&str>, Option<&str>) {
+    let Some(first) = phrases.first() else {
+        return (None, None);
+    };
+    let pair = (&first.source_language, &first.target_language);
+    if phrases
+        .iter()
+        .all(|phrase| (&phrase.source_language, &phrase.target_language) == pair)
+    {
+        (pair.0.as_deref(), pair.1.as_deref())
+    } else {
+        (None, None)
+    }
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Local};
+
+use crate::types::Direction;
+
+pub type PhraseId = i64;
+pub type OriginalSentence = String;
+pub type Translation = String;
+pub type Translations = Vec<Translation>;
+pub type Phrases = Vec<Phrase>;
+
+/// Separates alternative translations in a single text field, e.g. "Issue an appeal|Make an appeal".
+pub const TRANSLATION_SEPARATOR: char = '|';
+/// Separates tags in a single text field, e.g. "idiom;news".
+pub const TAG_SEPARATOR: char = ';';
+
+/// A phrase to learn together with its accepted translations and metadata.
+///
+/// The `id` is assigned by the storage: a hash of the deck and original text for CSV files,
+/// so editing the original text of a phrase changes its id, and a primary key for SQLite.
+/// Removing phrases leaves ids of the other ones as they were. Phrases not stored yet have
+/// an id of 0.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Phrase {
+    pub id: PhraseId,
+    pub original: OriginalSentence,
+    /// Accepted translations, the primary one first.
+    pub translations: Translations,
+    pub source_language: Option<String>,
+    pub target_language: Option<String>,
+    /// Name of the deck the phrase belongs to, if the storage groups phrases into decks.
+    pub deck: Option<String>,
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+    pub example: Option<String>,
+    /// Difficulty assigned by the deck author, from 1 (easy) to 5 (hard).
+    pub difficulty: Option<u8>,
+    pub created_at: Option<DateTime<Local>>,
+}
+
+impl Phrase {
+    pub fn new(original: OriginalSentence, translations: Translations) -> Self {
+        Phrase {
+            original,
+            translations,
+            ..Default::default()
+        }
+    }
+
+    /// Text shown to the user when asking in the given direction.
+    pub fn prompt(&self, direction: Direction) -> &str {
+        match direction {
+            Direction::Forward => &self.original,
+            Direction::Reverse => self
+                .translations
+                .first()
+                .map_or(self.original.as_str(), String::as_str),
+        }
+    }
+
+    /// Language of the text shown when asking in the given direction, if known.
+    pub fn prompt_language(&self, direction: Direction) -> Option<&str> {
+        match direction {
+            Direction::Forward => self.source_language.as_deref(),
+            Direction::Reverse => self.target_language.as_deref(),
+        }
+    }
+
+    /// Language of the answers expected when asking in the given direction, if known.
+    pub fn answer_language(&self, direction: Direction) -> Option<&str> {
+        match direction {
+            Direction::Forward => self.target_language.as_deref(),
+            Direction::Reverse => self.source_language.as_deref(),
+        }
+    }
+
+    /// Answers accepted when asking in the given direction.
+    pub fn answers(&self, direction: Direction) -> &[String] {
+        match direction {
+            Direction::Forward => &self.translations,
+            Direction::Reverse => std::slice::from_ref(&self.original),
+        }
+    }
+}
+
+/// Splits a text field into accepted translations, dropping empty entries.
+pub fn split_translations(field: &str) -> Translations {
+    field
+        .split(TRANSLATION_SEPARATOR)
+        .map(str::trim)
+        .filter(|translation| !translation.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+pub fn join_translations(translations: &[Translation]) -> String {
+    translations.join(&TRANSLATION_SEPARATOR.to_string())
+}
+
+/// Splits a text field into tags, dropping empty entries.
+pub fn split_tags(field: &str) -> Vec<String> {
+    field
+        .split(TAG_SEPARATOR)
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+pub fn join_tags(tags: &[String]) -> String {
+    tags.join(&TAG_SEPARATOR.to_string())
+}
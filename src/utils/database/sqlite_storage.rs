@@ -0,0 +1,367 @@
+use anyhow::Context;
+use chrono::DateTime;
+use log::{debug, trace};
+use rusqlite::{Connection, Transaction, params};
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use super::phrase::{Phrase, PhraseId, Phrases};
+use super::{Review, Storage};
+
+/// Schema migrations, applied in order. The index of the last applied migration is kept in
+/// SQLite's `user_version` pragma, so new migrations must only ever be appended.
+const MIGRATIONS: &[&str] = &[
+    r#"
+CREATE TABLE language_pairs (
+    id              INTEGER PRIMARY KEY,
+    source_language TEXT NOT NULL,
+    target_language TEXT NOT NULL,
+    UNIQUE (source_language, target_language)
+);
+
+CREATE TABLE phrases (
+    id               INTEGER PRIMARY KEY,
+    language_pair_id INTEGER REFERENCES language_pairs (id),
+    original         TEXT NOT NULL,
+    translation      TEXT NOT NULL,
+    created_at       TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    UNIQUE (original, translation)
+);
+
+CREATE TABLE tags (
+    id   INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE phrase_tags (
+    phrase_id INTEGER NOT NULL REFERENCES phrases (id) ON DELETE CASCADE,
+    tag_id    INTEGER NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+    PRIMARY KEY (phrase_id, tag_id)
+);
+
+CREATE TABLE reviews (
+    id          INTEGER PRIMARY KEY,
+    phrase_id   INTEGER NOT NULL REFERENCES phrases (id) ON DELETE CASCADE,
+    reviewed_at TEXT NOT NULL,
+    attempts    INTEGER NOT NULL,
+    recognized  INTEGER NOT NULL
+);
+
+CREATE INDEX reviews_phrase_id ON reviews (phrase_id);
+"#,
+    r#"
+CREATE TABLE alternative_translations (
+    phrase_id   INTEGER NOT NULL REFERENCES phrases (id) ON DELETE CASCADE,
+    position    INTEGER NOT NULL,
+    translation TEXT NOT NULL,
+    PRIMARY KEY (phrase_id, position)
+);
+"#,
+    r#"
+ALTER TABLE phrases ADD COLUMN notes TEXT;
+ALTER TABLE phrases ADD COLUMN example TEXT;
+ALTER TABLE phrases ADD COLUMN difficulty INTEGER;
+"#,
+    r#"
+ALTER TABLE reviews ADD COLUMN direction TEXT NOT NULL DEFAULT 'forward';
+"#,
+    r#"
+CREATE TABLE decks (
+    id   INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+
+ALTER TABLE phrases ADD COLUMN deck_id INTEGER REFERENCES decks (id);
+"#,
+    r#"
+ALTER TABLE reviews ADD COLUMN hints INTEGER NOT NULL DEFAULT 0;
+"#,
+];
+
+/// Storage backed by a SQLite database file, created and migrated on first open.
+pub struct SqliteStorage {
+    connection: Connection,
+}
+
+impl SqliteStorage {
+    pub fn open(path: &str) -> anyhow::Result<Self> {
+        let mut connection = Connection::open(path)
+            .with_context(|| format!("Failed to open SQLite database {}", path))?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+        SqliteStorage::migrate(&mut connection)?;
+
+        debug!("SQLite storage opened: {}", path);
+        Ok(SqliteStorage { connection })
+    }
+
+    fn migrate(connection: &mut Connection) -> anyhow::Result<()> {
+        let version: usize =
+            connection.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        trace!("SQLite schema version: {}", version);
+
+        for (idx, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            let transaction = connection.transaction()?;
+            transaction
+                .execute_batch(migration)
+                .with_context(|| format!("Failed to apply migration {}", idx + 1))?;
+            transaction.pragma_update(None, "user_version", idx + 1)?;
+            transaction.commit()?;
+            debug!("Applied SQLite migration {}", idx + 1);
+        }
+
+        Ok(())
+    }
+
+    /// Loads phrases together with their alternative translations, languages and tags,
+    /// keyed by phrase id.
+    fn load_phrases_by_id(&self) -> anyhow::Result<BTreeMap<PhraseId, Phrase>> {
+        let mut statement = self.connection.prepare(
+            "SELECT p.id, p.original, p.translation, l.source_language, l.target_language,
+                    p.notes, p.example, p.difficulty, p.created_at, d.name
+             FROM phrases p
+             LEFT JOIN language_pairs l ON l.id = p.language_pair_id
+             LEFT JOIN decks d ON d.id = p.deck_id",
+        )?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((
+                    Phrase {
+                        id: row.get(0)?,
+                        original: row.get(1)?,
+                        translations: vec![row.get(2)?],
+                        source_language: row.get(3)?,
+                        target_language: row.get(4)?,
+                        deck: row.get(9)?,
+                        tags: Vec::new(),
+                        notes: row.get(5)?,
+                        example: row.get(6)?,
+                        difficulty: row.get(7)?,
+                        created_at: None,
+                    },
+                    row.get::<_, String>(8)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut phrases = BTreeMap::new();
+        for (mut phrase, created_at) in rows {
+            phrase.created_at = DateTime::parse_from_rfc3339(&created_at)
+                .map(Into::into)
+                .ok();
+            phrases.insert(phrase.id, phrase);
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT phrase_id, translation FROM alternative_translations
+             ORDER BY phrase_id, position",
+        )?;
+        let alternatives = statement.query_map([], |row| {
+            Ok((row.get::<_, PhraseId>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for alternative in alternatives {
+            let (phrase_id, translation) = alternative?;
+            if let Some(phrase) = phrases.get_mut(&phrase_id) {
+                phrase.translations.push(translation);
+            }
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT pt.phrase_id, t.name FROM phrase_tags pt JOIN tags t ON t.id = pt.tag_id
+             ORDER BY pt.phrase_id, t.name",
+        )?;
+        let tags = statement.query_map([], |row| {
+            Ok((row.get::<_, PhraseId>(0)?, row.get::<_, String>(1)?))
+        })?;
+        for tag in tags {
+            let (phrase_id, tag) = tag?;
+            if let Some(phrase) = phrases.get_mut(&phrase_id) {
+                phrase.tags.push(tag);
+            }
+        }
+
+        Ok(phrases)
+    }
+
+    /// Inserts the phrase unless it's stored already. The first translation is the primary
+    /// one, the rest is kept as alternatives.
+    fn insert_phrase(transaction: &Transaction, phrase: &Phrase) -> anyhow::Result<bool> {
+        let (primary, alternatives) = phrase
+            .translations
+            .split_first()
+            .context("Phrase has no translations")?;
+        let language_pair_id = match (&phrase.source_language, &phrase.target_language) {
+            (Some(source), Some(target)) => {
+                transaction.execute(
+                    "INSERT OR IGNORE INTO language_pairs (source_language, target_language)
+                     VALUES (?1, ?2)",
+                    params![source, target],
+                )?;
+                Some(transaction.query_row(
+                    "SELECT id FROM language_pairs
+                     WHERE source_language = ?1 AND target_language = ?2",
+                    params![source, target],
+                    |row| row.get::<_, i64>(0),
+                )?)
+            }
+            _ => None,
+        };
+        let deck_id = match &phrase.deck {
+            Some(deck) => {
+                transaction.execute(
+                    "INSERT OR IGNORE INTO decks (name) VALUES (?1)",
+                    params![deck],
+                )?;
+                Some(transaction.query_row(
+                    "SELECT id FROM decks WHERE name = ?1",
+                    params![deck],
+                    |row| row.get::<_, i64>(0),
+                )?)
+            }
+            None => None,
+        };
+        let created_at = phrase.created_at.unwrap_or_else(chrono::Local::now);
+
+        let inserted = transaction.execute(
+            "INSERT OR IGNORE INTO phrases
+                 (original, translation, language_pair_id, deck_id, notes, example, difficulty,
+                  created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                phrase.original,
+                primary,
+                language_pair_id,
+                deck_id,
+                phrase.notes,
+                phrase.example,
+                phrase.difficulty,
+                created_at.to_rfc3339()
+            ],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+
+        let phrase_id = transaction.last_insert_rowid();
+        for (position, translation) in alternatives.iter().enumerate() {
+            transaction.execute(
+                "INSERT INTO alternative_translations (phrase_id, position, translation)
+                 VALUES (?1, ?2, ?3)",
+                params![phrase_id, position, translation],
+            )?;
+        }
+        for tag in &phrase.tags {
+            transaction.execute(
+                "INSERT OR IGNORE INTO tags (name) VALUES (?1)",
+                params![tag],
+            )?;
+            transaction.execute(
+                "INSERT OR IGNORE INTO phrase_tags (phrase_id, tag_id)
+                 SELECT ?1, id FROM tags WHERE name = ?2",
+                params![phrase_id, tag],
+            )?;
+        }
+        Ok(true)
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load_phrases(&self) -> anyhow::Result<Phrases> {
+        let phrases: Phrases = self.load_phrases_by_id()?.into_values().collect();
+
+        trace!("Loaded {} phrases from SQLite", phrases.len());
+        Ok(phrases)
+    }
+
+    fn remove_phrase(&mut self, id: PhraseId) -> anyhow::Result<bool> {
+        let removed = self
+            .connection
+            .execute("DELETE FROM phrases WHERE id = ?1", params![id])?;
+
+        debug!("Removed {} phrases with id {} from SQLite", removed, id);
+        Ok(removed > 0)
+    }
+
+    fn load_reviews(&self) -> anyhow::Result<Vec<Review>> {
+        let phrases = self.load_phrases_by_id()?;
+        let mut statement = self.connection.prepare(
+            "SELECT phrase_id, attempts, recognized, reviewed_at, direction, hints FROM reviews
+             ORDER BY reviewed_at, id",
+        )?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, String>(4)?,
+                    row.get(5)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut reviews = Vec::with_capacity(rows.len());
+        for (phrase_id, attempts, recognized, reviewed_at, direction, hints) in rows {
+            let phrase = phrases
+                .get(&phrase_id)
+                .with_context(|| format!("Review refers to unknown phrase {}", phrase_id))?;
+            let reviewed_at = DateTime::parse_from_rfc3339(&reviewed_at)
+                .context("Failed to parse review timestamp")?;
+            reviews.push(Review {
+                phrase: phrase.clone(),
+                direction: direction.parse()?,
+                attempts,
+                recognized,
+                hints,
+                reviewed_at: reviewed_at.into(),
+            });
+        }
+
+        trace!("Loaded {} reviews from SQLite", reviews.len());
+        Ok(reviews)
+    }
+
+    fn save_reviews(&mut self, reviews: &[Review]) -> anyhow::Result<()> {
+        let transaction = self.connection.transaction()?;
+        for review in reviews {
+            transaction.execute(
+                "INSERT INTO reviews
+                     (phrase_id, reviewed_at, attempts, recognized, direction, hints)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    review.phrase.id,
+                    review.reviewed_at.to_rfc3339(),
+                    review.attempts,
+                    review.recognized,
+                    review.direction.as_str(),
+                    review.hints
+                ],
+            )?;
+            trace!("Review saved: {:?}", review);
+        }
+        transaction.commit()?;
+
+        debug!("Saved {} reviews to SQLite", reviews.len());
+        Ok(())
+    }
+
+    /// Reviews are saved to the same file as the phrases, so its modification time can't
+    /// tell whether phrases changed.
+    fn phrases_modified_at(&self) -> anyhow::Result<Option<SystemTime>> {
+        Ok(None)
+    }
+
+    fn insert_phrases(&mut self, phrases: &Phrases) -> anyhow::Result<usize> {
+        let transaction = self.connection.transaction()?;
+        let mut inserted = 0;
+        for phrase in phrases {
+            if SqliteStorage::insert_phrase(&transaction, phrase)? {
+                inserted += 1;
+            }
+        }
+        transaction.commit()?;
+
+        debug!("Inserted {} new phrases into SQLite", inserted);
+        Ok(inserted)
+    }
+}
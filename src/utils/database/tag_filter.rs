@@ -0,0 +1,81 @@
+use std::str::FromStr;
+
+/// Expression selecting phrases by their tags.
+///
+/// Tags separated with whitespace must all match, alternatives are separated with `|` and
+/// a tag prefixed with `-` or `!` must not be present. AND binds tighter than OR, e.g.
+/// `news idiom | business -formal` selects phrases tagged both `news` and `idiom`, and
+/// `business` phrases not tagged `formal`. Tags are compared case-insensitively and an empty
+/// expression matches every phrase.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagFilter {
+    /// Alternatives of conditions that all have to hold, as (tag, whether it's required).
+    alternatives: Vec<Vec<(String, bool)>>,
+}
+
+impl TagFilter {
+    pub fn matches(&self, tags: &[String]) -> bool {
+        if self.alternatives.is_empty() {
+            return true;
+        }
+
+        let has_tag = |tag: &str| tags.iter().any(|other| other.to_lowercase() == tag);
+        self.alternatives.iter().any(|conditions| {
+            conditions
+                .iter()
+                .all(|(tag, required)| has_tag(tag) == *required)
+        })
+    }
+}
+
+impl FromStr for TagFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(expression: &str) -> anyhow::Result<Self> {
+        if expression.trim().is_empty() {
+            return Ok(TagFilter::default());
+        }
+
+        let mut alternatives = Vec::new();
+        for alternative in expression.split('|') {
+            let mut conditions = Vec::new();
+            for term in alternative.split_whitespace() {
+                let (tag, required) = match term.strip_prefix(['-', '!']) {
+                    Some(tag) => (tag, false),
+                    None => (term, true),
+                };
+                if tag.is_empty() {
+                    anyhow::bail!("Missing tag after '{}' in tag filter.", term);
+                }
+                conditions.push((tag.to_lowercase(), required));
+            }
+            if conditions.is_empty() {
+                anyhow::bail!("Empty alternative in tag filter '{}'.", expression);
+            }
+            alternatives.push(conditions);
+        }
+        Ok(TagFilter { alternatives })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(tags: &[&str]) -> Vec<String> {
+        tags.iter().map(|tag| tag.to_string()).collect()
+    }
+
+    #[test]
+    fn test_tag_filter() {
+        let filter: TagFilter = "news Idiom | business -formal".parse().unwrap();
+
+        assert!(filter.matches(&tags(&["idiom", "news"])));
+        assert!(!filter.matches(&tags(&["news"])));
+        assert!(filter.matches(&tags(&["Business"])));
+        assert!(!filter.matches(&tags(&["business", "formal"])));
+        assert!(TagFilter::default().matches(&[]));
+        assert!("news |".parse::<TagFilter>().is_err());
+        assert!("news -".parse::<TagFilter>().is_err());
+    }
+}